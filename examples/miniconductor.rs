@@ -9,14 +9,13 @@ use anyhow::{anyhow, Result};
 use compose_yml::v2 as dc;
 use regex::Regex;
 use std::env;
-use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::process;
 
 // Given a build context, ensure that it points to a local directory.
 fn git_to_local(ctx: &dc::Context) -> Result<PathBuf> {
     match ctx {
-        &dc::Context::GitUrl(ref url) => {
+        dc::Context::GitUrl(url) => {
             // Simulate a local checkout of the remote Git repository
             // mentioned in `build`.
             let re = Regex::new(r#"/([^./]+)(?:\.git)?"#).unwrap();
@@ -28,7 +27,7 @@ fn git_to_local(ctx: &dc::Context) -> Result<PathBuf> {
                 }
             }
         }
-        &dc::Context::Dir(ref dir) => Ok(dir.clone()),
+        dc::Context::Dir(dir) => Ok(dir.clone()),
     }
 }
 
@@ -98,7 +97,7 @@ fn main() {
         // We use `unwrap` here to turn I/O errors into application panics.
         // If we can't print a message to stderr without an I/O error,
         // the situation is hopeless.
-        write!(io::stderr(), "Error: {}", err).unwrap();
+        eprint!("Error: {}", err);
         process::exit(1);
     }
 }
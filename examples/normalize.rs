@@ -2,7 +2,7 @@
 //! normalized format.
 
 use compose_yml::v2 as dc;
-use std::io::{self, Write};
+use std::io;
 
 fn normalize() -> dc::Result<()> {
     let file = dc::File::read(io::stdin())?;
@@ -12,6 +12,6 @@ fn normalize() -> dc::Result<()> {
 
 fn main() {
     if let Err(ref err) = normalize() {
-        write!(io::stderr(), "Error: {}", err).unwrap();
+        eprint!("Error: {}", err);
     }
 }
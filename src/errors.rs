@@ -29,13 +29,31 @@ pub enum Error {
     #[error("could not convert {0:?} to the equivalent Windows path")]
     ConvertMountedPathToWindows(String),
 
+    /// Some services depend on each other in a cycle, so they can't be
+    /// started.  `cycle` lists the services in order, starting and ending
+    /// with the same service.
+    #[error("services have circular dependencies: {}", .cycle.join(" -> "))]
+    DependencyCycle { cycle: Vec<String> },
+
     /// A value did not conform to a JSON schema.
     #[error("data did not confirm to schema: {0}")]
     DoesNotConformToSchema(String),
 
     /// The interpolation syntax in the specified string was invalid.
-    #[error("invalid interpolation syntax {0:?}")]
-    InterpolateInvalidSyntax(String),
+    /// `column` is the 1-based position of the problem, counted in
+    /// characters.
+    #[error("invalid interpolation syntax {input:?} at column {column}: {message}")]
+    InterpolateInvalidSyntax {
+        input: String,
+        column: usize,
+        message: String,
+    },
+
+    /// The string requires an environment variable using `${VAR?message}`
+    /// or `${VAR:?message}`, but it is not set (or is empty, for the `:`
+    /// form).  `message` is the user's error message.
+    #[error("required environment variable {name:?} is missing: {message}")]
+    InterpolateRequiredVariable { name: String, message: String },
 
     /// The string contains an undefined environment variable.  This is not
     /// an error for `docker-compose` (which treats undefined variables as
@@ -53,6 +71,11 @@ pub enum Error {
     #[error("cannot parse without interpolating environment variables {0:?}")]
     InterpolationDisabled(String),
 
+    /// The `extends` field of a service could not be resolved, because
+    /// it refers to a missing service or is part of a cycle.
+    #[error("cannot resolve `extends` for service {service:?}: {message}")]
+    InvalidExtends { service: String, message: String },
+
     /// A string value in a `docker-compose.yml` file could not be
     /// parsed.
     #[error("invalid {wanted} {input:?}")]
@@ -61,6 +84,18 @@ pub enum Error {
     #[error("I/O error")]
     IoError(#[source] io::Error),
 
+    /// We were asked to read and merge a list of files, but the list was
+    /// empty.
+    #[error("at least one file is required")]
+    NoFiles,
+
+    /// An override file used `!override` on the list item at `path`.  List
+    /// items can't be replaced one at a time, because they may have moved
+    /// when the lists were merged.  Use `!override` on the whole list
+    /// instead.
+    #[error("cannot use `!override` on list item {path:?}; use it on the whole list instead")]
+    OverrideListItem { path: String },
+
     /// An `.env` file could not be parsed.
     #[error("cannot parse env variable declaration {line:?}")]
     ParseEnv { line: String },
@@ -80,6 +115,13 @@ pub enum Error {
         source: Box<dyn StdError + Send + Sync + 'static>,
     },
 
+    /// An override file used `!reset` on the list item at `path`.  List
+    /// items can't be removed one at a time, because they may have moved
+    /// when the lists were merged.  Use `!override` on the whole list
+    /// instead.
+    #[error("cannot use `!reset` on list item {path:?}; use `!override` on the whole list instead")]
+    ResetListItem { path: String },
+
     /// We don't support the specified version of `docker-compose.yml`.
     #[error("unsupported docker-compose.yml version {0:?}")]
     UnsupportedVersion(String),
@@ -45,6 +45,13 @@
 //! `docker_compose::v2::Service`.  You can drill down into other fields
 //! from there.
 //!
+//! Version 3 files, which add support for Docker Swarm features like
+//! `deploy`, `secrets` and `configs`, live in `compose_yml::v3`, and share
+//! most of their leaf types with `compose_yml::v2`.  Modern, versionless
+//! files following the Compose Specification live in `compose_yml::spec`.
+//! Legacy version 1 files can be converted to version 2 using
+//! `compose_yml::v1`.
+//!
 //! [dcv2]: https://docs.docker.com/compose/compose-file/
 
 // Enable as many useful Rust and Clippy warnings as we can stand.  We'd
@@ -62,4 +69,8 @@
 )]
 
 pub mod errors;
+#[macro_use]
 pub mod v2;
+pub mod spec;
+pub mod v1;
+pub mod v3;
@@ -0,0 +1,55 @@
+use super::common::*;
+
+/// Details about how a service depends on another service.  This is like
+/// `v2::Dependency`, but it also supports the `restart` and `required`
+/// fields.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dependency {
+    /// When should the dependency be considered ready?
+    pub condition: RawOr<DependencyCondition>,
+
+    /// Should this service be restarted when the dependency is updated?
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub restart: bool,
+
+    /// Is the dependency required?  If this is `Some(false)`, then
+    /// `docker compose` will only warn if the dependency can't be started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(Dependency, {
+    condition, restart, required, _hidden
+});
+
+impl Default for Dependency {
+    fn default() -> Dependency {
+        Dependency {
+            condition: value(DependencyCondition::default()),
+            restart: Default::default(),
+            required: Default::default(),
+            _hidden: (),
+        }
+    }
+}
+
+#[test]
+fn dependency_supports_restart_and_required() {
+    let yaml = r#"---
+condition: service_healthy
+restart: true
+required: false
+"#;
+    assert_roundtrip!(Dependency, yaml);
+    let dep: Dependency = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(dep.required, Some(false));
+}
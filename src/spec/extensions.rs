@@ -0,0 +1,106 @@
+//! Support for `x-*` extension fields.
+
+use serde::de::{self, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess};
+use std::fmt;
+use std::marker::PhantomData;
+
+use super::common::*;
+
+/// Extension fields, whose names start with `x-`.  These are ignored by
+/// `docker compose`, and are typically used to hold YAML anchors which are
+/// shared by several services.
+pub type Extensions = BTreeMap<String, serde_yaml::Value>;
+
+impl MergeOverride for serde_yaml::Value {}
+impl InterpolateAll for serde_yaml::Value {}
+
+/// A struct which may contain extension fields.  Types which implement this
+/// derive their own deserializer using `#[serde(remote = "Self")]`, which
+/// is wrapped by `deserialize_with_extensions`.
+pub(crate) trait HasExtensions: Sized {
+    /// Deserialize all the fields except extensions.
+    fn deserialize_fields<'de, D>(deserializer: D) -> result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+
+    /// The extension fields of this struct.
+    fn extensions_mut(&mut self) -> &mut Extensions;
+}
+
+/// Deserialize a `T`, collecting any `x-*` fields into its extensions.
+/// The other fields are passed straight through to the derived
+/// deserializer, without being buffered, so that `RawOr` can see the
+/// original text of unquoted YAML scalars.
+pub(crate) fn deserialize_with_extensions<'de, D, T>(
+    deserializer: D,
+) -> result::Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: HasExtensions,
+{
+    /// Declare an internal visitor type to handle our input.
+    struct ExtensionsVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: HasExtensions> de::Visitor<'de> for ExtensionsVisitor<T> {
+        type Value = T;
+
+        fn visit_map<M>(self, map: M) -> result::Result<T, M::Error>
+        where
+            M: MapAccess<'de>,
+        {
+            let mut extensions = Extensions::new();
+            let filter = ExtensionFilter {
+                map,
+                extensions: &mut extensions,
+            };
+            let mut value =
+                T::deserialize_fields(de::value::MapAccessDeserializer::new(filter))?;
+            *value.extensions_mut() = extensions;
+            Ok(value)
+        }
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(formatter, "a map")
+        }
+    }
+
+    deserializer.deserialize_map(ExtensionsVisitor(PhantomData))
+}
+
+/// A `MapAccess` which removes `x-*` entries from `map`, storing them in
+/// `extensions`.
+struct ExtensionFilter<'a, M> {
+    /// The map we're reading.
+    map: M,
+    /// The extension fields we've found.
+    extensions: &'a mut Extensions,
+}
+
+impl<'de, 'a, M: MapAccess<'de>> MapAccess<'de> for ExtensionFilter<'a, M> {
+    type Error = M::Error;
+
+    fn next_key_seed<K>(
+        &mut self,
+        seed: K,
+    ) -> result::Result<Option<K::Value>, M::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        while let Some(key) = self.map.next_key::<String>()? {
+            if key.starts_with("x-") {
+                let value = self.map.next_value()?;
+                self.extensions.insert(key, value);
+            } else {
+                return seed.deserialize(key.into_deserializer()).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> result::Result<V::Value, M::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        self.map.next_value_seed(seed)
+    }
+}
@@ -0,0 +1,257 @@
+use super::common::*;
+
+/// A Compose Specification file.  Unlike older formats, these files do not
+/// need to declare a `version`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields, remote = "Self")]
+pub struct File {
+    /// The obsolete `version` field.  This is accepted for backwards
+    /// compatibility, but it is ignored by the specification.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// The name of this project.  Defaults to the name of the directory
+    /// containing this file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<RawOr<String>>,
+
+    /// Other Compose files to include in this project.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_list_of_string_or_struct",
+        deserialize_with = "deserialize_list_of_string_or_struct"
+    )]
+    pub include: Vec<Include>,
+
+    /// The individual services which make up this app.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub services: BTreeMap<String, Service>,
+
+    /// Named volumes used by this app.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_map_struct_or_null"
+    )]
+    pub volumes: BTreeMap<String, Volume>,
+
+    /// The networks used by this app.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_map_struct_or_null"
+    )]
+    pub networks: BTreeMap<String, Network>,
+
+    /// Secrets which may be granted to services.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub secrets: BTreeMap<String, FileObject>,
+
+    /// Configuration files which may be granted to services.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub configs: BTreeMap<String, FileObject>,
+
+    /// Extension fields, whose names start with `x-`.
+    #[serde(flatten, skip_deserializing)]
+    pub extensions: Extensions,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(File, {
+    version, name, include, services, volumes, networks, secrets, configs,
+    extensions, _hidden
+});
+
+impl<'de> Deserialize<'de> for File {
+    fn deserialize<D>(deserializer: D) -> result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_with_extensions(deserializer)
+    }
+}
+
+impl Serialize for File {
+    fn serialize<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        File::serialize(self, serializer)
+    }
+}
+
+impl HasExtensions for File {
+    fn deserialize_fields<'de, D>(deserializer: D) -> result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        File::deserialize(deserializer)
+    }
+
+    fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
+}
+
+impl File {
+    /// Read a file from an input stream containing YAML.
+    pub fn read<R>(r: R) -> Result<Self>
+    where
+        R: io::Read,
+    {
+        let file = serde_yaml::from_reader(r)?;
+        validate_file(&file)?;
+        Ok(file)
+    }
+
+    /// Write a file to an output stream as YAML.
+    pub fn write<W>(&self, w: &mut W) -> Result<()>
+    where
+        W: io::Write,
+    {
+        validate_file(self)?;
+        Ok(serde_yaml::to_writer(w, self)?)
+    }
+
+    /// Write a file to an output stream as YAML, showing values which were
+    /// interpolated from sensitive variables in their redacted form.  This
+    /// is intended for logging and debugging, so unlike `write`, it doesn't
+    /// validate the file.  See `InterpolateOptions::sensitive`.
+    pub fn write_redacted<W>(&self, w: &mut W) -> Result<()>
+    where
+        W: io::Write,
+    {
+        Ok(with_redaction(|| serde_yaml::to_writer(w, self))?)
+    }
+
+    /// Read a file from the specified path.
+    pub fn read_from_path<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let f = fs::File::open(path)
+            .map_err(|err| Error::read_file(path.to_owned(), err))?;
+        Self::read(io::BufReader::new(f))
+            .map_err(|err| Error::read_file(path.to_owned(), err))
+    }
+
+    /// Write a file to the specified path.
+    pub fn write_to_path<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let f = fs::File::create(path)
+            .map_err(|err| Error::write_file(path.to_owned(), err))?;
+        self.write(&mut io::BufWriter::new(f))
+            .map_err(|err| Error::write_file(path.to_owned(), err))
+    }
+}
+
+impl FromStr for File {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<File> {
+        Self::read(io::Cursor::new(s))
+    }
+}
+
+#[test]
+fn file_can_be_converted_from_and_to_yaml_without_version() {
+    let yaml = r#"---
+configs:
+  nginx:
+    content: "worker_processes 1;"
+include:
+  - ../db/compose.yaml
+name: myapp
+secrets:
+  api_key:
+    environment: API_KEY
+services:
+  debug:
+    image: busybox
+    profiles:
+      - debug
+  web:
+    depends_on:
+      - db
+    image: "example/web:latest"
+    ports:
+      - "80:8080"
+    secrets:
+      - api_key
+"#;
+    assert_roundtrip!(File, yaml);
+
+    let file = File::from_str(yaml).unwrap();
+    assert_eq!(file.version, None);
+    let debug = file.services.get("debug").unwrap();
+    assert_eq!(debug.profiles[0].value().unwrap(), "debug");
+}
+
+#[test]
+fn file_keeps_extension_fields() {
+    let yaml = r#"---
+x-image: &image "example/web:latest"
+services:
+  web:
+    image: *image
+    x-owner: web-team
+"#;
+    assert_roundtrip!(File, yaml);
+    let file = File::from_str(yaml).unwrap();
+    assert!(file.extensions.contains_key("x-image"));
+    let web = file.services.get("web").unwrap();
+    assert_eq!(
+        web.image.as_ref().unwrap().value().unwrap().to_string(),
+        "example/web:latest"
+    );
+    assert_eq!(
+        web.extensions["x-owner"],
+        serde_yaml::Value::from("web-team")
+    );
+}
+
+#[test]
+fn file_rejects_unknown_fields() {
+    assert!(File::from_str(
+        "---
+servicess: {}
+"
+    )
+    .is_err());
+}
+
+#[test]
+fn file_ignores_obsolete_version_field() {
+    let yaml = r#"---
+services:
+  foo:
+    image: hello
+version: "3.8"
+"#;
+    assert_roundtrip!(File, yaml);
+    assert!(File::from_str(yaml).is_ok());
+}
+
+#[test]
+fn file_checks_fields_against_schema() {
+    // Project names must be lowercase.
+    let yaml = r#"---
+name: MyApp
+services:
+  foo:
+    image: hello
+"#;
+    assert!(File::from_str(yaml).is_err());
+}
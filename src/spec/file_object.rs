@@ -0,0 +1,65 @@
+use super::common::*;
+
+/// A top-level `secrets` or `configs` entry, which makes the contents of a
+/// file available to services.  This is like `v3::FileObject`, but it also
+/// allows the contents to come from an environment variable or to be
+/// written inline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct FileObject {
+    /// The name of this object in Docker, if it's different from the name
+    /// we use to refer to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<RawOr<String>>,
+
+    /// The local file containing this object's contents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<RawOr<PathBuf>>,
+
+    /// The name of an environment variable containing this object's
+    /// contents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<RawOr<String>>,
+
+    /// The literal contents of a config.  Only valid for `configs`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<RawOr<String>>,
+
+    /// If this is true, then this object was created outside of
+    /// `docker compose`.  This option is mutually exclusive with `file`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external: Option<bool>,
+
+    /// Docker labels for this object, specifying various sorts of custom
+    /// metadata.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_map_or_key_value_list"
+    )]
+    pub labels: BTreeMap<String, RawOr<String>>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(FileObject, {
+    name, file, environment, content, external, labels, _hidden
+});
+
+#[test]
+fn file_object_supports_environment_and_content() {
+    let yaml = r#"---
+environment: API_KEY
+"#;
+    assert_roundtrip!(FileObject, yaml);
+    let yaml = r#"---
+content: "worker_processes 1;"
+"#;
+    assert_roundtrip!(FileObject, yaml);
+}
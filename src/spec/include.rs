@@ -0,0 +1,121 @@
+use super::common::*;
+
+/// Another Compose file to include in this project, along with the
+/// services, networks and volumes that it defines.  This may be written
+/// using either a short syntax (just the path) or a long syntax (a
+/// struct).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Include {
+    /// The Compose files to include.  Multiple files will be merged
+    /// together as if they were overrides.
+    #[serde(deserialize_with = "deserialize_item_or_list")]
+    pub path: Vec<RawOr<PathBuf>>,
+
+    /// Environment files used to interpolate variables in the included
+    /// files.  Note that this is `env_file` in the underlying Docker
+    /// format, but the singular form looks weird at the API level.
+    #[serde(
+        rename = "env_file",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_item_or_list"
+    )]
+    pub env_files: Vec<RawOr<PathBuf>>,
+
+    /// The directory to use when resolving relative paths in the included
+    /// files.  Defaults to the directory containing the first file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_directory: Option<RawOr<PathBuf>>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(Include, {
+    path, env_files => "env_file", project_directory, _hidden
+});
+
+impl Include {
+    /// Include a single Compose file.  To override other fields, you can
+    /// use struct notation.
+    ///
+    /// ```
+    /// use compose_yml::spec as dc;
+    ///
+    /// dc::Include::new("../db/compose.yaml");
+    ///
+    /// dc::Include {
+    ///   project_directory: Some(dc::escape("../db").unwrap()),
+    ///   ..dc::Include::new("../db/compose.yaml")
+    /// };
+    /// ```
+    pub fn new<P: Into<PathBuf>>(path: P) -> Include {
+        Include {
+            path: vec![value(path.into())],
+            env_files: Default::default(),
+            project_directory: Default::default(),
+            _hidden: (),
+        }
+    }
+}
+
+impl FromStr for Include {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(Include {
+            path: vec![raw(s)?],
+            ..Include::new("")
+        })
+    }
+}
+
+impl SerializeStringOrStruct for Include {
+    fn serialize_string_or_struct<S>(
+        &self,
+        serializer: S,
+    ) -> result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.path.len() == 1
+            && self.env_files.is_empty()
+            && self.project_directory.is_none()
+        {
+            self.path[0].serialize(serializer)
+        } else {
+            self.serialize(serializer)
+        }
+    }
+}
+
+#[test]
+fn includes_may_be_strings_or_structs() {
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Wrapper {
+        #[serde(
+            deserialize_with = "deserialize_list_of_string_or_struct",
+            serialize_with = "serialize_list_of_string_or_struct"
+        )]
+        include: Vec<Include>,
+    }
+
+    let yaml = r#"---
+include:
+  - ../db/compose.yaml
+  - env_file: ../web/.env
+    path:
+      - ../web/compose.yaml
+      - ../web/compose.override.yaml
+"#;
+    let wrapper: Wrapper = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(wrapper.include[0], Include::new("../db/compose.yaml"));
+    assert_eq!(wrapper.include[1].path.len(), 2);
+    assert_eq!(wrapper.include[1].env_files.len(), 1);
+}
@@ -0,0 +1,80 @@
+//! Support for the versionless Compose Specification file format.
+//!
+//! Modern versions of `docker compose` no longer require a `version` field,
+//! and instead accept files following the [Compose Specification][spec].
+//! This format is essentially a union of versions 2 and 3, plus a few new
+//! features like `profiles`, `include` and a top-level project `name`.
+//!
+//! Wherever this format overlaps with older ones, we re-use the types from
+//! `compose_yml::v2` and `compose_yml::v3`.
+//!
+//! [spec]: https://github.com/compose-spec/compose-spec
+
+// Re-export errors here so that people can use them by including `use
+// compose_yml::spec`.
+pub use crate::errors::*;
+
+// Re-export the types we share with older file formats.
+pub use crate::v2::{
+    escape, raw, value, AliasedName, Build, CommandLine, Context, DependencyCondition,
+    DotEnvEnvironment, Environment, Extends, ExternalNetwork, FieldPath, GitUrl,
+    HostMapping, HostVolume, Image, ImageVersion, InterpolateAll, InterpolateOptions,
+    InterpolationRecord, IpcMode, LayeredEnvironment, Logging, MemorySize,
+    MergeOverride, NetworkInterface, NetworkMode, OsEnvironment, PidMode, PortMapping,
+    Ports, Protocol, RawOr, RawRewrite, RawVisitor, RawVisitorMut, RegistryHost,
+    RestartMode, SensitiveVariables, ServiceOrContainer, Ulimit, UndefinedVariable,
+    VariableReference, VariableSource, VariableUse, VolumeModes, VolumeMount,
+    VolumesFrom,
+};
+pub use crate::v3::{
+    BindOptions, Deploy, Healthcheck, Network, ObjectReference, Placement,
+    PlacementPreference, PortSpec, ResourceSpec, Resources, RestartPolicy,
+    ServicePort, ServiceVolume, TmpfsOptions, UpdateConfig, Volume, VolumeOptions,
+    VolumeSpec,
+};
+
+mod validate;
+
+// Basic file structure.
+mod dependency;
+mod extensions;
+mod file;
+mod file_object;
+mod include;
+mod service;
+
+// Re-export from our child modules.
+pub use dependency::*;
+pub use extensions::Extensions;
+pub use file::*;
+pub use file_object::*;
+pub use include::*;
+pub use service::*;
+
+mod common {
+    pub(crate) use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    pub(crate) use std::collections::BTreeMap;
+    pub(crate) use std::fs;
+    pub(crate) use std::io;
+    pub(crate) use std::path::{Path, PathBuf};
+    pub(crate) use std::result;
+    pub(crate) use std::str::FromStr;
+
+    pub(crate) use crate::v2::helpers::{
+        deserialize_item_or_list, deserialize_map_or_default_list,
+        deserialize_map_or_key_value_list, deserialize_map_struct_or_null, is_false,
+        serialize_map_or_default_list,
+    };
+    pub(crate) use crate::v2::interpolation::with_redaction;
+    pub(crate) use crate::v2::string_or_struct::{
+        deserialize_list_of_string_or_struct, deserialize_opt_string_or_struct,
+        serialize_list_of_string_or_struct, serialize_opt_string_or_struct,
+        SerializeStringOrStruct,
+    };
+    pub(crate) use crate::v2::{alias_key, host_mapping_key};
+    pub(crate) use crate::v3::{object_reference_key, service_volume_key};
+
+    pub(crate) use super::extensions::{deserialize_with_extensions, HasExtensions};
+    pub(crate) use super::validate::validate_file;
+    pub(crate) use super::*;
+}
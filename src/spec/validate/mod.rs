@@ -0,0 +1,26 @@
+//! Support for validating a Compose Specification file against the
+//! official schema.
+
+use lazy_static::lazy_static;
+use std::ops::Deref;
+
+use super::File;
+use crate::errors::*;
+use crate::v2::validate::{load_schema_json, validate_value};
+
+/// Schema for the Compose Specification.
+const COMPOSE_SPEC_SCHEMA_STR: &str = include_str!("compose-spec.json");
+
+lazy_static! {
+    /// Parsed schema for the Compose Specification.
+    static ref COMPOSE_SPEC_SCHEMA: serde_json::Value =
+    load_schema_json(COMPOSE_SPEC_SCHEMA_STR);
+}
+
+/// Validate a `File` against the official Compose Specification JSON
+/// schema.  The `version` field is obsolete and ignored by the
+/// specification, so we accept any value.
+pub fn validate_file(file: &File) -> Result<()> {
+    let value = serde_json::to_value(file).map_err(Error::validation_failed)?;
+    validate_value(COMPOSE_SPEC_SCHEMA.deref(), &value)
+}
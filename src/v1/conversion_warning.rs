@@ -0,0 +1,44 @@
+use super::common::*;
+
+/// Something in a version 1 file which could not be converted exactly
+/// to version 2.  The converted file is still usable, but it may behave
+/// differently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionWarning {
+    /// The service which caused this warning, or `None` if it applies to
+    /// the whole file.
+    pub service: Option<String>,
+
+    /// A human-readable description of the problem.
+    pub message: String,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    pub _hidden: (),
+}
+
+impl ConversionWarning {
+    /// Create a new warning.
+    pub(crate) fn new<S: Into<String>>(
+        service: Option<&str>,
+        message: S,
+    ) -> ConversionWarning {
+        ConversionWarning {
+            service: service.map(|s| s.to_owned()),
+            message: message.into(),
+            _hidden: (),
+        }
+    }
+}
+
+impl fmt::Display for ConversionWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.service {
+            Some(service) => write!(f, "service {}: {}", service, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
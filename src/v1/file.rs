@@ -0,0 +1,188 @@
+use super::common::*;
+
+/// A version 1 `docker-compose.yml` file, which is just a map of service
+/// names to services.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(transparent)]
+pub struct File {
+    /// The individual services which make up this app.
+    pub services: BTreeMap<String, Service>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(skip)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(File, {
+    services, _hidden
+});
+
+impl File {
+    /// Read a file from an input stream containing YAML.  Returns
+    /// `Error::UnsupportedVersion` if the file has a top-level `version`
+    /// key, because that means it isn't a version 1 file.
+    pub fn read<R>(r: R) -> Result<Self>
+    where
+        R: io::Read,
+    {
+        let text = read_text(r)?;
+        if let Some(vers) = declared_version(&text)? {
+            return Err(Error::UnsupportedVersion(vers));
+        }
+        Ok(serde_yaml::from_str(&text)?)
+    }
+
+    /// Convert this file to a version 2 file, returning any warnings about
+    /// things we couldn't convert exactly.
+    pub fn to_v2(&self) -> (v2::File, Vec<ConversionWarning>) {
+        let mut warnings = vec![];
+        let mut converted = v2::File {
+            version: "2".to_owned(),
+            ..Default::default()
+        };
+        for (name, service) in &self.services {
+            let service =
+                service.to_v2(name, self, &mut converted.volumes, &mut warnings);
+            converted.services.insert(name.clone(), service);
+        }
+        if self.services.values().any(|s| s.net.is_none()) {
+            warnings.push(ConversionWarning::new(
+                None,
+                "services without `net` will use a per-project network instead \
+                 of the default bridge network",
+            ));
+        }
+        (converted, warnings)
+    }
+}
+
+impl FromStr for File {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<File> {
+        Self::read(io::Cursor::new(s))
+    }
+}
+
+/// Read all of `r` into a string.  We deserialize files from the original
+/// text, because `serde_yaml::Value` doesn't keep the exact text of values
+/// like `1.10`.
+fn read_text<R: io::Read>(mut r: R) -> Result<String> {
+    let mut text = String::new();
+    r.read_to_string(&mut text).map_err(Error::IoError)?;
+    Ok(text)
+}
+
+/// If `text` has a top-level `version` key, return its value.  A version
+/// 1 file could theoretically have a service named `version`, but that
+/// would be a map and not a string.
+fn declared_version(text: &str) -> Result<Option<String>> {
+    let yaml: serde_yaml::Value = serde_yaml::from_str(text)?;
+    Ok(match yaml.get("version") {
+        Some(serde_yaml::Value::String(s)) => Some(s.to_owned()),
+        Some(serde_yaml::Value::Number(n)) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+/// Read a `docker-compose.yml` file which may use either version 1 or
+/// version 2 of the format, and return it as a version 2 file.  Version 1
+/// files will be converted using `File::to_v2`, and any warnings will be
+/// returned.
+pub fn read_as_v2<R>(r: R) -> Result<(v2::File, Vec<ConversionWarning>)>
+where
+    R: io::Read,
+{
+    let text = read_text(r)?;
+    if declared_version(&text)?.is_some() {
+        Ok((v2::File::from_str(&text)?, vec![]))
+    } else {
+        let file: File = serde_yaml::from_str(&text)?;
+        Ok(file.to_v2())
+    }
+}
+
+#[test]
+fn file_can_be_converted_to_version_2() {
+    let yaml = r#"---
+db:
+  image: postgres
+  volumes:
+    - pgdata:/var/lib/postgresql/data
+web:
+  build: .
+  links:
+    - db
+  log_driver: syslog
+  net: "container:db"
+  cpuset: "0,1"
+  mem_swappiness: 10
+  read_only: true
+  volumes_from:
+    - db
+    - data_container
+"#;
+    let file = File::from_str(yaml).unwrap();
+    let (converted, warnings) = file.to_v2();
+    v2::validate::validate_file(&converted).unwrap();
+
+    let web = &converted.services["web"];
+    assert_eq!(
+        web.network_mode.as_ref().unwrap().value().unwrap(),
+        &NetworkMode::Service("db".to_owned())
+    );
+    assert_eq!(
+        web.logging
+            .as_ref()
+            .unwrap()
+            .driver
+            .as_ref()
+            .unwrap()
+            .value()
+            .unwrap(),
+        "syslog"
+    );
+    assert_eq!(
+        web.volumes_from[1].value().unwrap(),
+        &VolumesFrom::container("data_container")
+    );
+    assert_eq!(converted.volumes["pgdata"].external, Some(true));
+    assert_eq!(web.cpuset, Some(value("0,1".to_owned())));
+    assert_eq!(web.mem_swappiness, Some(value(10)));
+    assert_eq!(web.read_only, Some(true));
+
+    // `db` uses the default network, which is different in version 2.
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].service, None);
+}
+
+#[test]
+fn file_warns_about_unsupported_fields() {
+    let yaml = r#"---
+web:
+  image: hello
+  net: host
+  dockerfile: Dockerfile.dev
+"#;
+    let (_, warnings) = File::from_str(yaml).unwrap().to_v2();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].service.as_deref(), Some("web"));
+}
+
+#[test]
+fn file_rejects_files_with_version() {
+    let yaml = r#"---
+services:
+  web:
+    image: hello
+version: "2"
+"#;
+    assert!(File::from_str(yaml).is_err());
+    let (file, warnings) = read_as_v2(yaml.as_bytes()).unwrap();
+    assert_eq!(file.version, "2");
+    assert!(warnings.is_empty());
+}
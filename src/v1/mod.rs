@@ -0,0 +1,63 @@
+//! Support for the legacy `docker-compose.yml` version 1 file format.
+//!
+//! Version 1 files have no `version` key, and they declare services at
+//! the top level of the file.  We don't support writing this format.
+//! Instead, we parse it and convert it to a `compose_yml::v2::File`,
+//! reporting anything which could not be converted exactly.
+//!
+//! ```
+//! use compose_yml::v1;
+//!
+//! let yaml = r#"---
+//! web:
+//!   image: "example/web"
+//!   log_driver: syslog
+//!   net: host
+//! "#;
+//!
+//! let (file, warnings) = v1::read_as_v2(yaml.as_bytes()).unwrap();
+//! assert_eq!(file.version, "2");
+//! assert!(file.services["web"].logging.is_some());
+//! assert!(warnings.is_empty());
+//! ```
+
+// Re-export errors here so that people can use them by including `use
+// compose_yml::v1`.
+pub use crate::errors::*;
+
+// Re-export the types we share with version 2 files.
+pub use crate::v2::{
+    escape, raw, value, AliasedName, CommandLine, Context, Environment, Extends,
+    HostMapping, HostVolume, Image, IpcMode, MemorySize, MergeOverride, NetworkMode,
+    PidMode, PortMapping, RawOr, RestartMode, ServiceOrContainer, Ulimit, VolumeMount,
+    VolumesFrom,
+};
+
+// Basic file structure.
+mod file;
+mod service;
+
+// Conversion support.
+mod conversion_warning;
+
+// Re-export from our child modules.
+pub use conversion_warning::*;
+pub use file::*;
+pub use service::*;
+
+mod common {
+    pub(crate) use serde::{Deserialize, Serialize};
+    pub(crate) use std::collections::BTreeMap;
+    pub(crate) use std::fmt;
+    pub(crate) use std::io;
+    pub(crate) use std::path::PathBuf;
+    pub(crate) use std::str::FromStr;
+
+    pub(crate) use crate::v2;
+    pub(crate) use crate::v2::helpers::{
+        deserialize_item_or_list, deserialize_map_or_key_value_list, is_false,
+    };
+    pub(crate) use crate::v2::{alias_key, host_mapping_key, volume_mount_key};
+
+    pub(crate) use super::*;
+}
@@ -0,0 +1,488 @@
+use super::common::*;
+
+/// A service in a version 1 `docker-compose.yml` file.  Many of these
+/// fields are identical to the ones in `v2::Service`, but a few were
+/// renamed or restructured in version 2.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Service {
+    /// The directory containing the `Dockerfile` used to build this
+    /// service.  Version 1 only supports the short form of `build`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build: Option<RawOr<Context>>,
+
+    /// The name of an alternate `Dockerfile` to use when building.  This
+    /// became `build.dockerfile` in version 2.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dockerfile: Option<RawOr<String>>,
+
+    /// A list of capability names to grant to this container.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cap_add: Vec<RawOr<String>>,
+
+    /// A list of capability names to revoke from this container.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cap_drop: Vec<RawOr<String>>,
+
+    /// The command-line to run when launching the container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<CommandLine>,
+
+    /// The name of an optional parent cgroup.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cgroup_parent: Option<RawOr<String>>,
+
+    /// An optional (global, non-scalable) container name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_name: Option<RawOr<String>>,
+
+    /// The relative CPU weight of this container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_shares: Option<RawOr<u32>>,
+
+    /// The CPU CFS quota for this container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_quota: Option<RawOr<u32>>,
+
+    /// The CPUs on which this container may run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpuset: Option<RawOr<String>>,
+
+    /// A list of devices to map into this container.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub devices: Vec<RawOr<AliasedName>>,
+
+    /// DNS servers.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_item_or_list"
+    )]
+    pub dns: Vec<RawOr<String>>,
+
+    /// Domains to search for hostnames.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_item_or_list"
+    )]
+    pub dns_search: Vec<RawOr<String>>,
+
+    /// The domain name to use for this container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domainname: Option<RawOr<String>>,
+
+    /// The entrypoint for the container (wraps `command`, basically).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entrypoint: Option<CommandLine>,
+
+    /// Environment files used to supply variables to the container.
+    #[serde(
+        rename = "env_file",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_item_or_list"
+    )]
+    pub env_files: Vec<RawOr<PathBuf>>,
+
+    /// Environment variables and values to supply to the container.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_map_or_key_value_list"
+    )]
+    pub environment: BTreeMap<String, RawOr<String>>,
+
+    /// Expose a list of ports to any containers that link to us.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expose: Vec<RawOr<String>>,
+
+    /// A service to use as the base for this one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extends: Option<Extends>,
+
+    /// Links to external containers.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub external_links: Vec<RawOr<AliasedName>>,
+
+    /// Mappings for extra hosts in /etc/hosts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_hosts: Vec<RawOr<HostMapping>>,
+
+    /// The hostname to use for this container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<RawOr<String>>,
+
+    /// The name of the image to build or pull for this container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<RawOr<Image>>,
+
+    /// What IPC namespacing mode should we use?
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipc: Option<RawOr<IpcMode>>,
+
+    /// Docker labels for this container.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_map_or_key_value_list"
+    )]
+    pub labels: BTreeMap<String, RawOr<String>>,
+
+    /// Links to other services in this file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<RawOr<AliasedName>>,
+
+    /// The logging driver to use.  This became `logging.driver` in
+    /// version 2.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_driver: Option<RawOr<String>>,
+
+    /// Options to pass to the logging driver.  This became
+    /// `logging.options` in version 2.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub log_opt: BTreeMap<String, RawOr<String>>,
+
+    /// The MAC address to use for this container's network interface.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mac_address: Option<RawOr<String>>,
+
+    /// The memory limit for this container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mem_limit: Option<RawOr<MemorySize>>,
+
+    /// The total limit for memory and swap for this container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memswap_limit: Option<RawOr<MemorySize>>,
+
+    /// How likely the kernel is to swap out this container's memory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mem_swappiness: Option<RawOr<u32>>,
+
+    /// What networking mode should we use?  This became `network_mode` in
+    /// version 2.  Note that `container:NAME` may refer to a service in
+    /// this file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub net: Option<RawOr<NetworkMode>>,
+
+    /// What PID namespacing mode should we use?
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<RawOr<PidMode>>,
+
+    /// What ports do we want to map to our host system?
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<RawOr<PortMapping>>,
+
+    /// Should this container be run in privileged mode?
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub privileged: bool,
+
+    /// Should the container's root file system be mounted read-only?
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub read_only: bool,
+
+    /// What should we do when the container exits?
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart: Option<RawOr<RestartMode>>,
+
+    /// Security options for AppArmor or SELinux.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub security_opt: Vec<RawOr<String>>,
+
+    /// The amount of shared memory to allocate for this container.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shm_size: Option<RawOr<MemorySize>>,
+
+    /// Should STDIN be left open when running the container?
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub stdin_open: bool,
+
+    /// The name of the Unix signal which will be sent to stop this
+    /// container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_signal: Option<RawOr<String>>,
+
+    /// Should a TTY be be allocated for the container?
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub tty: bool,
+
+    /// Resource limits to apply to the container.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub ulimits: BTreeMap<String, Ulimit>,
+
+    /// The user name (or UID) of the user under which to execute the
+    /// container's command.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<RawOr<String>>,
+
+    /// The volume driver used for any named volumes in `volumes`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume_driver: Option<RawOr<String>>,
+
+    /// Volumes associated with this service.  In version 1, volumes which
+    /// aren't paths are named volumes which are shared between projects.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub volumes: Vec<RawOr<VolumeMount>>,
+
+    /// Other places to get volumes from.  In version 1, a bare name may
+    /// refer to either a service or a container.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub volumes_from: Vec<RawOr<VolumesFrom>>,
+
+    /// The working directory to use for this container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<RawOr<String>>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(Service, {
+    build,
+    dockerfile,
+    cap_add with unique,
+    cap_drop with unique,
+    command,
+    cgroup_parent,
+    container_name,
+    cpu_shares,
+    cpu_quota,
+    cpuset,
+    devices with key(alias_key),
+    dns with unique,
+    dns_search with unique,
+    domainname,
+    entrypoint,
+    env_files => "env_file" with unique,
+    environment,
+    expose with unique,
+    extends,
+    external_links with unique,
+    extra_hosts with key(host_mapping_key),
+    hostname,
+    image,
+    ipc,
+    labels,
+    links with key(alias_key),
+    log_driver,
+    log_opt,
+    mac_address,
+    mem_limit,
+    memswap_limit,
+    mem_swappiness,
+    net,
+    pid,
+    ports with unique,
+    privileged,
+    read_only,
+    restart,
+    security_opt with unique,
+    shm_size,
+    stdin_open,
+    stop_signal,
+    tty,
+    ulimits,
+    user,
+    volume_driver,
+    volumes with key(volume_mount_key),
+    volumes_from with unique,
+    working_dir,
+    _hidden
+});
+
+impl Service {
+    /// Convert this service to a `v2::Service`.  `name` is the name of this
+    /// service, and `file` is the file containing it, which we need to
+    /// resolve references to other services.  Named volumes will be added
+    /// to `volumes`, and any problems will be added to `warnings`.
+    pub(crate) fn to_v2(
+        &self,
+        name: &str,
+        file: &File,
+        volumes: &mut BTreeMap<String, v2::Volume>,
+        warnings: &mut Vec<ConversionWarning>,
+    ) -> v2::Service {
+        let mut warn =
+            |msg: String| warnings.push(ConversionWarning::new(Some(name), msg));
+
+        // Version 1 only has the short form of `build`.
+        let build = match &self.build {
+            Some(context) => {
+                let mut build = v2::Build::new(Context::new("."));
+                build.context = context.clone();
+                build.dockerfile = self.dockerfile.clone();
+                Some(build)
+            }
+            None => {
+                if self.dockerfile.is_some() {
+                    warn("`dockerfile` has no effect without `build`".to_owned());
+                }
+                None
+            }
+        };
+
+        // `log_driver` and `log_opt` were combined into `logging`.
+        let logging = if self.log_driver.is_some() || !self.log_opt.is_empty() {
+            Some(v2::Logging {
+                driver: self.log_driver.clone(),
+                options: self.log_opt.clone(),
+                ..Default::default()
+            })
+        } else {
+            None
+        };
+
+        // `net: "container:NAME"` may refer to a service, which version 2
+        // spells `service:NAME`.
+        let network_mode = self.net.clone().map(|mut net| {
+            if let Ok(mode) = net.value_mut() {
+                if let NetworkMode::Container(other) = mode {
+                    if file.services.contains_key(other) {
+                        *mode = NetworkMode::Service(other.clone());
+                    }
+                }
+            }
+            net
+        });
+
+        // Bare names in `volumes_from` may refer to containers, which
+        // version 2 requires us to mark explicitly.
+        let volumes_from = self
+            .volumes_from
+            .iter()
+            .cloned()
+            .map(|mut vf| {
+                if let Ok(vf) = vf.value_mut() {
+                    if let ServiceOrContainer::Service(other) = &vf.source {
+                        if !file.services.contains_key(other) {
+                            vf.source = ServiceOrContainer::Container(other.clone());
+                        }
+                    }
+                }
+                vf
+            })
+            .collect();
+
+        // Named volumes in version 1 aren't namespaced by project, so the
+        // closest equivalent is an external volume.
+        for mount in &self.volumes {
+            match mount.value() {
+                Ok(VolumeMount {
+                    host: Some(HostVolume::Name(volume)),
+                    ..
+                }) => {
+                    if let Some(driver) = &self.volume_driver {
+                        warn(format!(
+                            "named volume `{}` is declared as external, so it \
+                             must already exist using volume driver `{}`",
+                            volume, driver,
+                        ));
+                    }
+                    volumes.insert(
+                        volume.clone(),
+                        v2::Volume {
+                            external: Some(true),
+                            ..Default::default()
+                        },
+                    );
+                }
+                Ok(_) => {}
+                Err(_) => warn(format!(
+                    "cannot tell whether `{}` is a named volume, because it \
+                     contains variables",
+                    mount,
+                )),
+            }
+        }
+
+        if let Some(extends) = &self.extends {
+            if extends.file.is_some() {
+                warn(
+                    "`extends` refers to another file, which must also be \
+                     converted to version 2"
+                        .to_owned(),
+                );
+            }
+        }
+
+        // Some `v2::Service` fields are private, so we can't use a struct
+        // literal here.
+        let mut service = v2::Service::default();
+        service.build = build;
+        service.cap_add = self.cap_add.clone();
+        service.cap_drop = self.cap_drop.clone();
+        service.command = self.command.clone();
+        service.cgroup_parent = self.cgroup_parent.clone();
+        service.container_name = self.container_name.clone();
+        service.devices = self.devices.clone();
+        service.dns = self.dns.clone();
+        service.dns_search = self.dns_search.clone();
+        service.entrypoint = self.entrypoint.clone();
+        service.env_files = self.env_files.clone();
+        service.environment = self.environment.clone();
+        service.expose = self.expose.clone();
+        service.extends = self.extends.clone();
+        service.external_links = self.external_links.clone();
+        service.extra_hosts = self.extra_hosts.clone();
+        service.image = self.image.clone();
+        service.labels = self.labels.clone();
+        service.links = self.links.clone();
+        service.logging = logging;
+        service.network_mode = network_mode;
+        service.pid = self.pid.clone();
+        service.ports = self.ports.clone();
+        service.security_opt = self.security_opt.clone();
+        service.stop_signal = self.stop_signal.clone();
+        service.ulimits = self.ulimits.clone();
+        service.volumes = self.volumes.clone();
+        service.volumes_from = volumes_from;
+        service.volume_driver = self.volume_driver.clone();
+        service.cpu_shares = self.cpu_shares.clone();
+        service.cpu_quota = self.cpu_quota.clone();
+        service.cpuset = self.cpuset.clone();
+        service.domainname = self.domainname.clone();
+        service.hostname = self.hostname.clone();
+        service.ipc = self.ipc.clone();
+        service.mac_address = self.mac_address.clone();
+        service.mem_limit = self.mem_limit.clone();
+        service.memswap_limit = self.memswap_limit.clone();
+        service.mem_swappiness = self.mem_swappiness.clone();
+        service.privileged = flag(self.privileged);
+        service.read_only = flag(self.read_only);
+        service.restart = self.restart.clone();
+        service.shm_size = self.shm_size.clone();
+        service.stdin_open = flag(self.stdin_open);
+        service.tty = flag(self.tty);
+        service.user = self.user.clone();
+        service.working_dir = self.working_dir.clone();
+        service
+    }
+}
+
+/// Convert a version 1 flag.  We can't tell whether a `false` flag was
+/// written explicitly, so we only set flags which are `true`.
+fn flag(value: bool) -> Option<bool> {
+    if value {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+#[test]
+fn service_handles_version_1_fields() {
+    let yaml = r#"---
+build: ./app
+dockerfile: Dockerfile-alt
+log_driver: syslog
+log_opt:
+  syslog-address: "tcp://192.168.0.42:123"
+net: "container:db"
+"#;
+    assert_roundtrip!(Service, yaml);
+}
@@ -23,6 +23,11 @@ impl AliasedName {
         Ok(result)
     }
 
+    /// The name of the external resource.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
     /// (Internal.) Validate an aliased name is safely serializeable.
     fn validate(&self) -> Result<()> {
         let bad_name = self.name.contains(':');
@@ -41,6 +46,15 @@ impl AliasedName {
 
 impl_interpolatable_value!(AliasedName);
 
+/// Identify an aliased name by its alias, or by its name if it has no
+/// alias.  This is how `docker-compose` merges `links` and `devices`,
+/// where the alias is the name used inside the container.
+pub(crate) fn alias_key(name: &RawOr<AliasedName>) -> Option<String> {
+    name.value()
+        .ok()
+        .map(|n| n.alias.as_ref().unwrap_or(&n.name).clone())
+}
+
 impl fmt::Display for AliasedName {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.alias {
@@ -78,7 +78,7 @@ impl SerializeStringOrStruct for Build {
     where
         S: Serializer,
     {
-        if self.dockerfile.is_none() && self.args.is_empty() {
+        if self.dockerfile.is_none() && self.args.is_empty() && self.target.is_none() {
             self.context.serialize(serializer)
         } else {
             self.serialize(serializer)
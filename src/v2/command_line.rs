@@ -1,7 +1,7 @@
 use super::common::*;
 
 /// A command line to be executed by Docker.
-#[derive(Debug, Deserialize, Clone, Eq, PartialEq, Serialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
 #[serde(untagged)]
 pub enum CommandLine {
     /// A command-line specified as unparsed shell code.
@@ -14,6 +14,46 @@ pub enum CommandLine {
     Parsed(Vec<RawOr<String>>),
 }
 
+/// We can't use `#[serde(untagged)]` to deserialize this, because it would
+/// buffer the items of a `Parsed` command line, losing the original text
+/// of unquoted YAML scalars like `1.10`.
+impl<'de> Deserialize<'de> for CommandLine {
+    fn deserialize<D>(deserializer: D) -> result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        /// Declare an internal visitor type to handle our input.
+        struct CommandLineVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for CommandLineVisitor {
+            type Value = CommandLine;
+
+            fn visit_str<E>(self, value: &str) -> result::Result<CommandLine, E>
+            where
+                E: serde::de::Error,
+            {
+                RawOr::from_str(value)
+                    .map(CommandLine::ShellCode)
+                    .map_err(|err| E::custom(format!("{}", err)))
+            }
+
+            fn visit_seq<A>(self, seq: A) -> result::Result<CommandLine, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let sd = serde::de::value::SeqAccessDeserializer::new(seq);
+                Deserialize::deserialize(sd).map(CommandLine::Parsed)
+            }
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(formatter, "a string or a list of strings")
+            }
+        }
+
+        deserializer.deserialize_any(CommandLineVisitor)
+    }
+}
+
 impl MergeOverride for CommandLine {}
 impl InterpolateAll for CommandLine {}
 
@@ -33,3 +73,16 @@ fn command_line_may_be_parsed() {
 "#;
     assert_roundtrip!(CommandLine, yaml);
 }
+
+#[test]
+fn command_line_items_keep_their_original_text() {
+    let cmd: CommandLine = serde_yaml::from_str("---\n[sleep, 10, 1.10]\n").unwrap();
+    assert_eq!(
+        cmd,
+        CommandLine::Parsed(vec![
+            value("sleep".to_owned()),
+            value("10".to_owned()),
+            value("1.10".to_owned()),
+        ])
+    );
+}
@@ -0,0 +1,115 @@
+use super::common::*;
+
+/// When should a service's dependency be considered ready?
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DependencyCondition {
+    /// The dependency has been started.  This is the default.
+    #[default]
+    ServiceStarted,
+    /// The dependency has passed its `healthcheck`.
+    ServiceHealthy,
+    /// The dependency has run to completion and exited successfully.
+    ServiceCompletedSuccessfully,
+}
+
+impl_interpolatable_value!(DependencyCondition);
+
+impl fmt::Display for DependencyCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencyCondition::ServiceStarted => write!(f, "service_started"),
+            DependencyCondition::ServiceHealthy => write!(f, "service_healthy"),
+            DependencyCondition::ServiceCompletedSuccessfully => {
+                write!(f, "service_completed_successfully")
+            }
+        }
+    }
+}
+
+impl FromStr for DependencyCondition {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "service_started" => Ok(DependencyCondition::ServiceStarted),
+            "service_healthy" => Ok(DependencyCondition::ServiceHealthy),
+            "service_completed_successfully" => {
+                Ok(DependencyCondition::ServiceCompletedSuccessfully)
+            }
+            _ => Err(Error::invalid_value("dependency condition", s)),
+        }
+    }
+}
+
+/// Details about how a service depends on another service.  A `depends_on`
+/// list is equivalent to a map where every value is
+/// `Dependency::default()`.  Older file versions only support the list
+/// syntax.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dependency {
+    /// When should the dependency be considered ready?
+    pub condition: RawOr<DependencyCondition>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(Dependency, {
+    condition, _hidden
+});
+
+impl Default for Dependency {
+    fn default() -> Dependency {
+        Dependency {
+            condition: value(DependencyCondition::default()),
+            _hidden: (),
+        }
+    }
+}
+
+#[test]
+fn dependency_condition_has_a_string_representation() {
+    let pairs = vec![
+        (DependencyCondition::ServiceStarted, "service_started"),
+        (DependencyCondition::ServiceHealthy, "service_healthy"),
+        (
+            DependencyCondition::ServiceCompletedSuccessfully,
+            "service_completed_successfully",
+        ),
+    ];
+    for (condition, s) in pairs {
+        assert_eq!(condition.to_string(), s);
+        assert_eq!(condition, DependencyCondition::from_str(s).unwrap());
+    }
+}
+
+#[test]
+fn dependencies_may_be_lists_or_maps() {
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Wrapper {
+        #[serde(
+            deserialize_with = "deserialize_map_or_default_list",
+            serialize_with = "serialize_map_or_default_list"
+        )]
+        depends_on: BTreeMap<String, Dependency>,
+    }
+
+    assert_roundtrip!(Wrapper, "---\ndepends_on:\n  - cache\n  - db\n");
+
+    let yaml = r#"---
+depends_on:
+  cache:
+    condition: service_started
+  db:
+    condition: service_healthy
+"#;
+    assert_roundtrip!(Wrapper, yaml);
+    let wrapper: Wrapper = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(wrapper.depends_on["cache"], Dependency::default());
+}
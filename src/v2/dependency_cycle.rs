@@ -0,0 +1,210 @@
+//! Finding cycles in the dependencies between services.
+
+use std::collections::BTreeSet;
+
+use super::common::*;
+use super::interpolation::InterpolatableValue;
+
+/// Find a cycle in the dependencies between the services in `file`, if
+/// there is one.  See `File::check_dependency_cycles`.
+pub(crate) fn find_dependency_cycle(file: &File) -> Option<Vec<String>> {
+    // Containers can be referred to by their `container_name`.
+    let containers: BTreeMap<&str, &str> = file
+        .services
+        .iter()
+        .filter_map(|(name, service)| {
+            let container = service.container_name.as_ref()?.value().ok()?;
+            Some((&container[..], &name[..]))
+        })
+        .collect();
+    let edges = file
+        .services
+        .iter()
+        .map(|(name, service)| {
+            let deps = service_dependencies(service, &containers)
+                .into_iter()
+                .filter(|dep| file.services.contains_key(dep))
+                .collect();
+            (name.to_owned(), deps)
+        })
+        .collect();
+
+    let mut search = Search {
+        edges: &edges,
+        done: BTreeSet::new(),
+        stack: vec![],
+    };
+    edges.keys().find_map(|name| search.visit(name))
+}
+
+/// The services which `service` needs to have started first.  These may
+/// include services which aren't defined.
+fn service_dependencies(
+    service: &Service,
+    containers: &BTreeMap<&str, &str>,
+) -> BTreeSet<String> {
+    let mut refs: Vec<ServiceOrContainer> = vec![];
+    for link in &service.links {
+        if let Ok(link) = link.value() {
+            refs.push(ServiceOrContainer::Service(link.name().to_owned()));
+        }
+    }
+    for volumes_from in &service.volumes_from {
+        if let Ok(volumes_from) = volumes_from.value() {
+            refs.push(volumes_from.source.clone());
+        }
+    }
+    refs.extend(mode_reference(&service.network_mode));
+    refs.extend(mode_reference(&service.pid));
+    refs.extend(mode_reference(&service.ipc));
+
+    let referenced = refs.into_iter().filter_map(|r| match r {
+        ServiceOrContainer::Service(name) => Some(name),
+        ServiceOrContainer::Container(name) => {
+            containers.get(&name[..]).map(|s| (*s).to_owned())
+        }
+    });
+    service
+        .depends_on
+        .keys()
+        .cloned()
+        .chain(referenced)
+        .collect()
+}
+
+/// The service or container whose namespace is shared by a mode like
+/// `network_mode: service:db` or `ipc: container:db`, if any.
+fn mode_reference<T>(mode: &Option<RawOr<T>>) -> Option<ServiceOrContainer>
+where
+    T: InterpolatableValue + SharedNamespace,
+{
+    mode.as_ref()?.value().ok()?.shared_with()
+}
+
+/// A mode which may share a namespace with another service or container.
+trait SharedNamespace {
+    /// The service or container whose namespace we share, if any.
+    fn shared_with(&self) -> Option<ServiceOrContainer>;
+}
+
+impl SharedNamespace for NetworkMode {
+    fn shared_with(&self) -> Option<ServiceOrContainer> {
+        match self {
+            NetworkMode::Bridge | NetworkMode::Host | NetworkMode::None => None,
+            NetworkMode::Service(name) => {
+                Some(ServiceOrContainer::Service(name.to_owned()))
+            }
+            NetworkMode::Container(name) => {
+                Some(ServiceOrContainer::Container(name.to_owned()))
+            }
+        }
+    }
+}
+
+impl SharedNamespace for PidMode {
+    fn shared_with(&self) -> Option<ServiceOrContainer> {
+        match self {
+            PidMode::Host => None,
+            PidMode::Service(name) => {
+                Some(ServiceOrContainer::Service(name.to_owned()))
+            }
+            PidMode::Container(name) => {
+                Some(ServiceOrContainer::Container(name.to_owned()))
+            }
+        }
+    }
+}
+
+impl SharedNamespace for IpcMode {
+    fn shared_with(&self) -> Option<ServiceOrContainer> {
+        match self {
+            IpcMode::Host => None,
+            IpcMode::Service(name) => {
+                Some(ServiceOrContainer::Service(name.to_owned()))
+            }
+            IpcMode::Container(name) => {
+                Some(ServiceOrContainer::Container(name.to_owned()))
+            }
+        }
+    }
+}
+
+/// A depth-first search for cycles.
+struct Search<'a> {
+    /// The dependencies of each service.
+    edges: &'a BTreeMap<String, BTreeSet<String>>,
+    /// Services which we know aren't part of a cycle.
+    done: BTreeSet<&'a str>,
+    /// The services we're currently visiting, in order.
+    stack: Vec<&'a str>,
+}
+
+impl<'a> Search<'a> {
+    /// Visit `name` and its dependencies, returning the first cycle we
+    /// find.
+    fn visit(&mut self, name: &'a str) -> Option<Vec<String>> {
+        if let Some(pos) = self.stack.iter().position(|s| *s == name) {
+            let mut cycle: Vec<String> =
+                self.stack[pos..].iter().map(|s| (*s).to_owned()).collect();
+            cycle.push(name.to_owned());
+            return Some(cycle);
+        }
+        if self.done.contains(name) {
+            return None;
+        }
+        self.stack.push(name);
+        let edges = self.edges;
+        if let Some(deps) = edges.get(name) {
+            for dep in deps {
+                if let Some(cycle) = self.visit(dep) {
+                    return Some(cycle);
+                }
+            }
+        }
+        self.stack.pop();
+        self.done.insert(name);
+        None
+    }
+}
+
+#[test]
+fn dependency_cycles_are_found_across_fields() {
+    let mut file = File::from_str(
+        r#"---
+services:
+  web:
+    image: "example/web"
+    depends_on: [app]
+  app:
+    image: "example/app"
+    container_name: app_1
+    links: ["db:database"]
+    pid: "service:web"
+  db:
+    image: "postgres"
+    volumes_from: ["container:logs"]
+  logs:
+    image: "example/logs"
+    container_name: logs
+version: "2.1"
+"#,
+    )
+    .unwrap();
+    let cycle = |file: &File| find_dependency_cycle(file).map(|c| c.join(" -> "));
+    assert_eq!(cycle(&file).as_deref(), Some("app -> web -> app"));
+
+    file.services.get_mut("app").unwrap().pid = None;
+    assert_eq!(cycle(&file), None);
+
+    file.services.get_mut("logs").unwrap().ipc =
+        Some(value(IpcMode::Container("app_1".to_owned())));
+    assert_eq!(cycle(&file).as_deref(), Some("app -> db -> logs -> app"));
+
+    let logs = file.services.get_mut("logs").unwrap();
+    logs.ipc = None;
+    logs.network_mode = Some(value(NetworkMode::Service("web".to_owned())));
+    assert_eq!(
+        cycle(&file).as_deref(),
+        Some("app -> db -> logs -> web -> app")
+    );
+}
@@ -0,0 +1,221 @@
+//! Comparing two files and describing the differences.
+
+use serde_json::Value;
+
+use super::common::*;
+
+/// What kind of change was made to a field.  Values are shown in the same
+/// form as they would be written to a file, converted to JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// A service was added.
+    ServiceAdded,
+    /// A service was removed.
+    ServiceRemoved,
+    /// A field or map entry was added with the specified value.
+    Added(Value),
+    /// A field or map entry was removed.  This contains the old value.
+    Removed(Value),
+    /// A field changed value.
+    Changed {
+        /// The value in the old file.
+        old: Value,
+        /// The value in the new file.
+        new: Value,
+    },
+    /// An item was added to a list.
+    ListItemAdded(Value),
+    /// An item was removed from a list.
+    ListItemRemoved(Value),
+}
+
+/// A single difference between two files, as returned by `File::diff`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// The changed field.  This refers to the whole service if a service
+    /// was added or removed.  Lists are identified by their field, not by
+    /// index, so paths stay the same when items are added.
+    pub path: FieldPath,
+
+    /// What changed.
+    pub kind: ChangeKind,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    pub _hidden: (),
+}
+
+impl Change {
+    /// Describe a change to the field at `path`, which is relative to the
+    /// top of the file.
+    fn new(path: &[String], kind: ChangeKind) -> Change {
+        Change {
+            path: FieldPath::new(path),
+            kind,
+            _hidden: (),
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.path)?;
+        match &self.kind {
+            ChangeKind::ServiceAdded => write!(f, "service added"),
+            ChangeKind::ServiceRemoved => write!(f, "service removed"),
+            ChangeKind::Added(value) => write!(f, "added {}", value),
+            ChangeKind::Removed(value) => write!(f, "removed {}", value),
+            ChangeKind::Changed { old, new } => {
+                write!(f, "changed from {} to {}", old, new)
+            }
+            ChangeKind::ListItemAdded(value) => write!(f, "added item {}", value),
+            ChangeKind::ListItemRemoved(value) => {
+                write!(f, "removed item {}", value)
+            }
+        }
+    }
+}
+
+/// Compare `old` and `new`.  See `File::diff`.
+pub(crate) fn diff(old: &File, new: &File) -> Result<Vec<Change>> {
+    let old = serde_json::to_value(old).map_err(Error::validation_failed)?;
+    let new = serde_json::to_value(new).map_err(Error::validation_failed)?;
+    let mut changes = vec![];
+    diff_values(&old, &new, &mut vec![], &mut changes);
+    Ok(changes)
+}
+
+/// Record the changes between `old` and `new`, which are found at `path`.
+fn diff_values(
+    old: &Value,
+    new: &Value,
+    path: &mut Vec<String>,
+    changes: &mut Vec<Change>,
+) {
+    if old == new {
+        return;
+    }
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            let mut keys: Vec<&String> =
+                old_map.keys().chain(new_map.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                path.push(key.to_owned());
+                match (old_map.get(key), new_map.get(key)) {
+                    (Some(o), Some(n)) => diff_values(o, n, path, changes),
+                    (Some(o), None) => changes.push(removed(path, o)),
+                    (None, Some(n)) => changes.push(added(path, n)),
+                    (None, None) => unreachable!("key should be in one map"),
+                }
+                path.pop();
+            }
+        }
+        (Value::Array(old_items), Value::Array(new_items))
+            if !is_reordering(old_items, new_items) =>
+        {
+            for item in old_items.iter().filter(|i| !new_items.contains(i)) {
+                let kind = ChangeKind::ListItemRemoved(item.clone());
+                changes.push(Change::new(path, kind));
+            }
+            for item in new_items.iter().filter(|i| !old_items.contains(i)) {
+                let kind = ChangeKind::ListItemAdded(item.clone());
+                changes.push(Change::new(path, kind));
+            }
+        }
+        _ => changes.push(Change::new(
+            path,
+            ChangeKind::Changed {
+                old: old.clone(),
+                new: new.clone(),
+            },
+        )),
+    }
+}
+
+/// A change recording that `value` was added at `path`.
+fn added(path: &[String], value: &Value) -> Change {
+    if path.len() == 2 && path[0] == "services" {
+        Change::new(path, ChangeKind::ServiceAdded)
+    } else {
+        Change::new(path, ChangeKind::Added(value.clone()))
+    }
+}
+
+/// A change recording that `value` was removed from `path`.
+fn removed(path: &[String], value: &Value) -> Change {
+    if path.len() == 2 && path[0] == "services" {
+        Change::new(path, ChangeKind::ServiceRemoved)
+    } else {
+        Change::new(path, ChangeKind::Removed(value.clone()))
+    }
+}
+
+/// Do `old` and `new` contain the same items in a different order?  We
+/// report these as a change to the whole list, because the order of items
+/// in fields like `command` matters.
+fn is_reordering(old: &[Value], new: &[Value]) -> bool {
+    old.len() == new.len()
+        && old.iter().all(|i| new.contains(i))
+        && new.iter().all(|i| old.contains(i))
+}
+
+#[test]
+fn diff_reports_typed_changes() {
+    let old = File::from_str(
+        r#"---
+services:
+  web:
+    image: "example/web:1.0"
+    command: ["run", "--fast"]
+    environment:
+      MODE: prod
+      LOG: info
+    ports: ["80:80", "443:443"]
+  worker:
+    image: "example/worker"
+version: "2"
+"#,
+    )
+    .unwrap();
+    let new = File::from_str(
+        r#"---
+services:
+  web:
+    image: "example/web:2.0"
+    command: ["--fast", "run"]
+    environment:
+      MODE: dev
+      DEBUG: "1"
+    ports: ["443:443", "8080:80"]
+  db:
+    image: "postgres"
+version: "2"
+"#,
+    )
+    .unwrap();
+    let changes: Vec<_> = old
+        .diff(&new)
+        .unwrap()
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            "services.db: service added",
+            r#"services.web.command: changed from ["run","--fast"] to ["--fast","run"]"#,
+            r#"services.web.environment.DEBUG: added "1""#,
+            r#"services.web.environment.LOG: removed "info""#,
+            r#"services.web.environment.MODE: changed from "prod" to "dev""#,
+            r#"services.web.image: changed from "example/web:1.0" to "example/web:2.0""#,
+            r#"services.web.ports: removed item "80:80""#,
+            r#"services.web.ports: added item "8080:80""#,
+            "services.worker: service removed",
+        ]
+    );
+}
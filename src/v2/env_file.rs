@@ -9,6 +9,7 @@ use std::path::Path;
 use super::common::*;
 
 /// A file pointed to by an `env_file:` field.
+#[derive(Debug)]
 pub struct EnvFile {
     /// The variables found in our env file.
     vars: BTreeMap<String, String>,
@@ -53,6 +54,11 @@ impl EnvFile {
             .map_err(|err| Error::read_file(path.to_owned(), err))
     }
 
+    /// Look up a variable in this `EnvFile`.
+    pub fn var(&self, key: &str) -> Option<&str> {
+        self.vars.get(key).map(|val| val.as_str())
+    }
+
     /// Convert this `EnvFile` to the format we use for the `environment`
     /// member of `Service`.
     pub fn to_environment(&self) -> Result<BTreeMap<String, RawOr<String>>> {
@@ -42,6 +42,117 @@ impl Extends {
     }
 }
 
+/// Resolves the `extends` fields in a file, loading other files as needed.
+pub(crate) struct ExtendsResolver<'a> {
+    /// The file containing the services we're resolving.
+    file: &'a File,
+    /// The directory containing `file`.
+    base: &'a Path,
+    /// Other files we've loaded, indexed by their canonical path.  Paths
+    /// in these files have been rewritten to be relative to `base`.
+    files: BTreeMap<PathBuf, File>,
+    /// The services we're currently resolving, used to detect cycles.
+    /// `None` refers to `file`.
+    stack: Vec<(Option<PathBuf>, String)>,
+}
+
+impl<'a> ExtendsResolver<'a> {
+    /// Create a resolver for the services in `file`, which is stored in
+    /// `base`.
+    pub(crate) fn new(file: &'a File, base: &'a Path) -> Self {
+        ExtendsResolver {
+            file,
+            base,
+            files: BTreeMap::new(),
+            stack: vec![],
+        }
+    }
+
+    /// Return a copy of the service `name` in `file` with its `extends`
+    /// applied.
+    pub(crate) fn resolve(
+        &mut self,
+        file: Option<&Path>,
+        name: &str,
+    ) -> Result<Service> {
+        let key = (file.map(|f| f.to_owned()), name.to_owned());
+        if self.stack.contains(&key) {
+            let mut chain: Vec<_> = self.stack.iter().map(|(_, n)| &n[..]).collect();
+            chain.push(name);
+            return Err(Error::InvalidExtends {
+                service: self.stack[0].1.clone(),
+                message: format!("circular reference {}", chain.join(" -> ")),
+            });
+        }
+
+        let mut service = self.lookup(file, name)?.clone();
+        if let Some(extends) = service.extends.take() {
+            let parent_file = match &extends.file {
+                Some(path) => Some(self.load(path.value()?)?),
+                None => file.map(|f| f.to_owned()),
+            };
+            self.stack.push(key);
+            let mut parent =
+                self.resolve(parent_file.as_deref(), extends.service.value()?)?;
+            self.stack.pop();
+
+            // `docker-compose` never shares these between services.
+            parent.links.clear();
+            parent.volumes_from.clear();
+            parent.depends_on.clear();
+            if let Some(Ok(NetworkMode::Service(_))) =
+                parent.network_mode.as_ref().map(|mode| mode.value())
+            {
+                parent.network_mode = None;
+            }
+            service = parent.merge_override(&service);
+        }
+        Ok(service)
+    }
+
+    /// Look up the service `name` in `file`, which must already be loaded.
+    fn lookup(&self, file: Option<&Path>, name: &str) -> Result<&Service> {
+        let services = match file {
+            None => &self.file.services,
+            Some(path) => &self.files[path].services,
+        };
+        services.get(name).ok_or_else(|| Error::InvalidExtends {
+            service: self
+                .stack
+                .first()
+                .map_or_else(|| name.to_owned(), |(_, n)| n.clone()),
+            message: match file {
+                None => format!("no service {:?}", name),
+                Some(path) => {
+                    format!("no service {:?} in {}", name, path.display())
+                }
+            },
+        })
+    }
+
+    /// Load the file at `path`, relative to `base`, and return its
+    /// canonical path.
+    fn load(&mut self, path: &Path) -> Result<PathBuf> {
+        let path = self.base.join(path);
+        let canonical = path
+            .canonicalize()
+            .map_err(|err| Error::read_file(path.clone(), err))?;
+        if !self.files.contains_key(&canonical) {
+            let mut file = File::read_from_path(&path)?;
+            let base = self
+                .base
+                .canonicalize()
+                .map_err(|err| Error::read_file(self.base.to_owned(), err))?;
+            let prefix = relative_dir(&base, &canonical_dir(&path)?);
+            if prefix != Path::new("") {
+                file.rebase_paths(&prefix);
+            }
+            self.files.insert(canonical.clone(), file);
+        }
+        Ok(canonical)
+    }
+}
+
 #[test]
 fn extends_can_be_roundtripped() {
     let yaml = r#"---
@@ -50,3 +161,94 @@ service: foo
 "#;
     assert_roundtrip!(Extends, yaml);
 }
+
+#[test]
+fn extends_can_be_resolved_within_a_file() {
+    let yaml = r#"---
+services:
+  base:
+    image: "example/web"
+    environment:
+      MODE: base
+    links: ["db"]
+    network_mode: "service:db"
+  web:
+    extends:
+      service: base
+    environment:
+      PORT: "80"
+  worker:
+    extends:
+      service: web
+    network_mode: "host"
+  db:
+    image: "postgres"
+  loop1:
+    extends:
+      service: loop2
+  loop2:
+    extends:
+      service: loop1
+version: "2"
+"#;
+    let mut file = File::from_str(yaml).unwrap();
+    match file.clone().resolve_extends(Path::new(".")) {
+        Err(Error::InvalidExtends { service, message }) => {
+            assert_eq!(service, "loop1");
+            assert_eq!(message, "circular reference loop1 -> loop2 -> loop1");
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    file.services.remove("loop1");
+    file.services.remove("loop2");
+    file.resolve_extends(Path::new(".")).unwrap();
+    let web = &file.services["web"];
+    assert!(web.extends.is_none());
+    assert_eq!(web.image, Some(value(Image::new("example/web").unwrap())));
+    assert_eq!(web.environment.len(), 2);
+    assert!(web.links.is_empty());
+    assert!(web.network_mode.is_none());
+    let worker = &file.services["worker"];
+    assert_eq!(worker.network_mode, Some(value(NetworkMode::Host)));
+}
+
+#[test]
+fn extends_can_load_other_files() {
+    let dir = TestDir::new("extends");
+    fs::create_dir_all(dir.join("common")).unwrap();
+    fs::write(
+        dir.join("common/common.yml"),
+        r#"---
+services:
+  app:
+    extends:
+      service: base
+    env_file: app.env
+  base:
+    build: .
+version: "2"
+"#,
+    )
+    .unwrap();
+    let yaml = r#"---
+services:
+  web:
+    extends:
+      file: common/common.yml
+      service: app
+    volumes_from: ["data"]
+  data:
+    image: "busybox"
+version: "2"
+"#;
+    let mut file = File::from_str(yaml).unwrap();
+    file.resolve_extends(&dir).unwrap();
+    let web = &file.services["web"];
+    assert_eq!(
+        web.build.as_ref().unwrap().context,
+        value(Context::new("common"))
+    );
+    assert_eq!(web.env_files, vec![value(PathBuf::from("common/app.env"))]);
+    assert_eq!(web.volumes_from.len(), 1);
+}
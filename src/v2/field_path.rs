@@ -0,0 +1,79 @@
+use super::common::*;
+
+/// The location of a field in a file, as reported by things like
+/// `File::diff` and `File::convert_to_version`.  Displaying a `FieldPath`
+/// gives the full path, like `services.web.environment.MODE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldPath {
+    /// The service containing this field, or `None` if the field is
+    /// outside of `services`.
+    pub service: Option<String>,
+
+    /// The path to the field, separated by `.`.  For fields in a service,
+    /// this is relative to the service, and it's empty if the path refers
+    /// to the whole service.  List items are identified by index, like
+    /// `links.0`.
+    pub field: String,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    pub _hidden: (),
+}
+
+impl FieldPath {
+    /// Describe the field at `path`, which is relative to the top of the
+    /// file.
+    pub(crate) fn new<S: ToString>(path: &[S]) -> FieldPath {
+        let path: Vec<String> = path.iter().map(|elem| elem.to_string()).collect();
+        match &path[..] {
+            [top, service, rest @ ..] if top == "services" => {
+                FieldPath::in_service(service, &rest.join("."))
+            }
+            _ => FieldPath {
+                service: None,
+                field: path.join("."),
+                _hidden: (),
+            },
+        }
+    }
+
+    /// Describe `field`, which is relative to `service`.
+    pub(crate) fn in_service(service: &str, field: &str) -> FieldPath {
+        FieldPath {
+            service: Some(service.to_owned()),
+            field: field.to_owned(),
+            _hidden: (),
+        }
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.service {
+            Some(service) if self.field.is_empty() => {
+                write!(f, "services.{}", service)
+            }
+            Some(service) => write!(f, "services.{}.{}", service, self.field),
+            None => write!(f, "{}", self.field),
+        }
+    }
+}
+
+#[test]
+fn field_paths_are_split_at_the_service() {
+    let path = |p: &str| FieldPath::new(&p.split('.').collect::<Vec<_>>());
+    let examples = vec![
+        ("services.web.links.0", Some("web"), "links.0"),
+        ("services.web", Some("web"), ""),
+        ("volumes.data.driver", None, "volumes.data.driver"),
+    ];
+    for (input, service, field) in examples {
+        let found = path(input);
+        assert_eq!(found.service.as_deref(), service);
+        assert_eq!(found.field, field);
+        assert_eq!(found.to_string(), input);
+    }
+}
@@ -61,6 +61,17 @@ impl File {
         Ok(serde_yaml::to_writer(w, self)?)
     }
 
+    /// Write a file to an output stream as YAML, showing values which were
+    /// interpolated from sensitive variables in their redacted form.  This
+    /// is intended for logging and debugging, so unlike `write`, it doesn't
+    /// validate the file.  See `InterpolateOptions::sensitive`.
+    pub fn write_redacted<W>(&self, w: &mut W) -> Result<()>
+    where
+        W: io::Write,
+    {
+        Ok(with_redaction(|| serde_yaml::to_writer(w, self))?)
+    }
+
     /// Read a file from the specified path.
     pub fn read_from_path<P>(path: P) -> Result<Self>
     where
@@ -73,6 +84,131 @@ impl File {
             .map_err(|err| Error::read_file(path.to_owned(), err))
     }
 
+    /// Read several files and merge them in order, the way `docker-compose
+    /// -f a.yml -f b.yml` does.  Relative paths in each file are resolved
+    /// relative to that file's directory, and then rewritten relative to
+    /// the directory of the first file.  The merged file uses the newest
+    /// `version` of any of the files.
+    ///
+    /// Later files may tag values with `!reset` to remove a field or map
+    /// entry from the merged file, or with `!override` to replace a list
+    /// or map wholesale instead of merging it.  Neither tag can be used on
+    /// a single list item, because items may have moved when the lists
+    /// were merged, so we return an error instead.  Tag the whole list
+    /// with `!override` to replace it.
+    ///
+    /// Returns `Error::NoFiles` if `paths` is empty.
+    pub fn read_all<P>(paths: &[P]) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::read_all_impl(paths, None)
+    }
+
+    /// Like `read_all`, but also record which file set each field of the
+    /// merged file.  This is useful for finding out why an override didn't
+    /// do what we expected.
+    ///
+    /// ```no_run
+    /// use compose_yml::v2 as dc;
+    ///
+    /// let (file, provenance) = dc::File::read_all_with_provenance(&[
+    ///     "docker-compose.yml",
+    ///     "docker-compose.override.yml",
+    /// ])
+    /// .unwrap();
+    /// if let Some(source) = provenance.source("services.web.image") {
+    ///     println!("image set by {}", source);
+    /// }
+    /// print!("{}", provenance);
+    /// ```
+    pub fn read_all_with_provenance<P>(paths: &[P]) -> Result<(Self, MergeProvenance)>
+    where
+        P: AsRef<Path>,
+    {
+        let mut provenance = MergeProvenance::default();
+        let file = Self::read_all_impl(paths, Some(&mut provenance))?;
+        Ok((file, provenance))
+    }
+
+    /// Implementation of `read_all`, recording provenance if requested.
+    fn read_all_impl<P>(
+        paths: &[P],
+        mut provenance: Option<&mut MergeProvenance>,
+    ) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let mut merged: Option<File> = None;
+        let mut base: Option<PathBuf> = None;
+        let mut minor = 0;
+        for path in paths {
+            let path = path.as_ref();
+            let text = fs::read_to_string(path)
+                .map_err(|err| Error::read_file(path.to_owned(), err))?;
+            let directives = MergeDirectives::parse(&text);
+            let mut file = directives
+                .read_file(&text)
+                .and_then(|file| {
+                    validate_file(&file)?;
+                    Ok(file)
+                })
+                .map_err(|err| Error::read_file(path.to_owned(), err))?;
+            let dir = canonical_dir(path)?;
+            match &base {
+                None => base = Some(dir),
+                Some(base) => {
+                    let prefix = relative_dir(base, &dir);
+                    if prefix != Path::new("") {
+                        file.rebase_paths(&prefix);
+                    }
+                }
+            }
+            minor = minor.max(
+                minor_version(&file.version)
+                    .map_err(|err| Error::read_file(path.to_owned(), err))?,
+            );
+            let mut next = match &merged {
+                Some(merged) => merged.merge_override(&file),
+                None => file.clone(),
+            };
+            directives
+                .apply(&mut next, &file)
+                .map_err(|err| Error::read_file(path.to_owned(), err))?;
+            next.version = version_string(minor);
+            if let Some(provenance) = provenance.as_deref_mut() {
+                provenance.record(path, &text, merged.as_ref(), &file, &next)?;
+            }
+            merged = Some(next);
+        }
+        let merged = merged.ok_or(Error::NoFiles)?;
+        validate_file(&merged)?;
+        Ok(merged)
+    }
+
+    /// Read the file at `path`, and merge in `docker-compose.override.yml`
+    /// (or `docker-compose.override.yaml`) from the same directory if it
+    /// exists.  This is what `docker-compose` does when no `-f` options
+    /// are given.
+    pub fn read_with_override<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let mut paths = vec![path.to_owned()];
+        paths.extend(
+            [
+                "docker-compose.override.yml",
+                "docker-compose.override.yaml",
+            ]
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|candidate| candidate.is_file()),
+        );
+        Self::read_all(&paths)
+    }
+
     /// Write a file to the specified path.
     pub fn write_to_path<P>(&self, path: P) -> Result<()>
     where
@@ -94,22 +230,346 @@ impl File {
         Ok(())
     }
 
+    /// Replace every service which uses `extends` with a copy of the
+    /// service it extends, merged with its own fields using the usual
+    /// `docker-compose` rules.  Files named by `Extends::file` are looked
+    /// up relative to `base`, and relative paths in those files are
+    /// rewritten to be relative to `base`.  Like `docker-compose`, we
+    /// never inherit `links`, `volumes_from`, `depends_on` or a
+    /// `network_mode` of `service:<name>`.  The resulting file doesn't use
+    /// `extends`.
+    pub fn resolve_extends(&mut self, base: &Path) -> Result<()> {
+        let mut resolver = ExtendsResolver::new(self, base);
+        let mut services = BTreeMap::new();
+        for name in self.services.keys() {
+            services.insert(name.to_owned(), resolver.resolve(None, name)?);
+        }
+        self.services = services;
+        Ok(())
+    }
+
     /// Convert this file to a standalone file, with no dependencies on the
     /// current environment or any external files.  This does _not_ lock
     /// down the image versions used in this file.
     pub fn make_standalone(&mut self, base: &Path) -> Result<()> {
+        self.make_standalone_env(base, &OsEnvironment::new())
+    }
+
+    /// Like `make_standalone`, but looking up variables in `env` instead
+    /// of the system environment.
+    pub fn make_standalone_env(
+        &mut self,
+        base: &Path,
+        env: &dyn Environment,
+    ) -> Result<()> {
+        self.make_standalone_audited(base, env)?;
+        Ok(())
+    }
+
+    /// Like `make_standalone_env`, but return a record of each field we
+    /// interpolated.  See `InterpolateAll::interpolate_all_audited`.
+    pub fn make_standalone_audited(
+        &mut self,
+        base: &Path,
+        env: &dyn Environment,
+    ) -> Result<Vec<InterpolationRecord>> {
         // We need to interpolate first, in case there are environment
         // variables being used to construct the paths to `env_files`
         // entries.
-        self.interpolate_all()?;
-        self.inline_all(base)
+        let records =
+            self.interpolate_all_audited(env, &InterpolateOptions::default())?;
+        self.inline_all(base)?;
+        Ok(records)
+    }
+
+    /// The earliest version of the file format which supports all the
+    /// fields used in this file, according to the official schema for
+    /// each version.
+    ///
+    /// ```
+    /// use compose_yml::v2 as dc;
+    ///
+    /// let mut file = dc::File::default();
+    /// assert_eq!(file.version, "2.4");
+    /// assert_eq!(file.minimum_version().unwrap(), "2");
+    ///
+    /// let mut build = dc::Build::new(dc::Context::new("."));
+    /// build.target = Some(dc::escape("prod").unwrap());
+    /// let mut service = dc::Service::default();
+    /// service.build = Some(build);
+    /// file.services.insert("web".to_owned(), service);
+    /// assert_eq!(file.minimum_version().unwrap(), "2.3");
+    /// ```
+    pub fn minimum_version(&self) -> Result<String> {
+        Ok(version_string(minimum_minor_version(self)?))
+    }
+
+    /// Set `version` to the value returned by `minimum_version`, to make
+    /// this file compatible with as many versions of `docker-compose` as
+    /// possible.
+    pub fn normalize_version(&mut self) -> Result<()> {
+        self.version = self.minimum_version()?;
+        Ok(())
+    }
+
+    /// Find references to services, named volumes and networks which
+    /// aren't defined in this file.  Unlike `validate_file`, which only
+    /// checks the schema, this looks at `depends_on`, `links`,
+    /// `volumes_from`, `network_mode`, `extends`, `volumes` and `networks`
+    /// in each service.  Values which still need to be interpolated are
+    /// skipped.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use compose_yml::v2 as dc;
+    ///
+    /// let file = dc::File::from_str(r#"---
+    /// version: "2"
+    /// services:
+    ///   web:
+    ///     image: "example/web"
+    ///     depends_on: [db]
+    /// "#).unwrap();
+    /// let problems = file.check_references();
+    /// assert_eq!(problems[0].path.to_string(), "services.web.depends_on.db");
+    /// assert_eq!(problems[0].kind, dc::ReferenceKind::Service);
+    /// ```
+    pub fn check_references(&self) -> Vec<InvalidReference> {
+        find_invalid_references(self)
+    }
+
+    /// Check that the services in this file don't depend on each other in
+    /// a cycle, which would prevent `docker-compose up` from starting
+    /// them.  Dependencies come from `depends_on`, `links`,
+    /// `volumes_from`, and `service:` references in `network_mode`, `pid`
+    /// and `ipc`.  References to containers count as dependencies if they
+    /// match the `container_name` of a service.  If we find a cycle, we
+    /// return `Error::DependencyCycle`.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use compose_yml::v2 as dc;
+    ///
+    /// let file = dc::File::from_str(r#"---
+    /// version: "2"
+    /// services:
+    ///   web:
+    ///     image: "example/web"
+    ///     links: [db]
+    ///   db:
+    ///     image: "postgres"
+    ///     volumes_from: [web]
+    /// "#).unwrap();
+    /// match file.check_dependency_cycles() {
+    ///     Err(dc::Error::DependencyCycle { cycle }) => {
+    ///         assert_eq!(cycle, vec!["db", "web", "db"]);
+    ///     }
+    ///     other => panic!("unexpected result: {:?}", other),
+    /// }
+    /// ```
+    pub fn check_dependency_cycles(&self) -> Result<()> {
+        match find_dependency_cycle(self) {
+            Some(cycle) => Err(Error::DependencyCycle { cycle }),
+            None => Ok(()),
+        }
+    }
+
+    /// Compare this file to `new`, and describe what changed.  Changes are
+    /// sorted by field, and each one has a stable path which doesn't
+    /// depend on the order of list items.
+    ///
+    /// ```
+    /// use std::collections::BTreeSet;
+    /// use std::str::FromStr;
+    /// use compose_yml::v2 as dc;
+    ///
+    /// let old = dc::File::from_str(r#"---
+    /// version: "2"
+    /// services:
+    ///   web:
+    ///     image: "example/web:1.0"
+    ///   db:
+    ///     image: "postgres"
+    /// "#).unwrap();
+    /// let mut new = old.clone();
+    /// new.services.get_mut("web").unwrap().image =
+    ///     Some(dc::value(dc::Image::new("example/web:2.0").unwrap()));
+    ///
+    /// // Find the services which need to be recreated.
+    /// let changed: BTreeSet<_> = old
+    ///     .diff(&new)
+    ///     .unwrap()
+    ///     .into_iter()
+    ///     .filter_map(|change| change.path.service)
+    ///     .collect();
+    /// assert_eq!(changed.into_iter().collect::<Vec<_>>(), vec!["web"]);
+    /// ```
+    pub fn diff(&self, new: &File) -> Result<Vec<Change>> {
+        diff(self, new)
+    }
+
+    /// Find the smallest override file which turns `base` into `target`
+    /// when merged using `MergeOverride`.  Some changes, like removing a
+    /// field or removing an item from a list which is merged by appending,
+    /// can't be expressed as an override.  These are returned as a list
+    /// of `InexpressibleChange`s, and the override includes whatever part
+    /// of the change it can.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use compose_yml::v2 as dc;
+    /// use compose_yml::v2::MergeOverride;
+    ///
+    /// let base = dc::File::from_str(r#"---
+    /// version: "2"
+    /// services:
+    ///   web:
+    ///     image: "example/web:1.0"
+    ///     ports: ["80:80"]
+    /// "#).unwrap();
+    /// let mut target = base.clone();
+    /// target.services.get_mut("web").unwrap().image =
+    ///     Some(dc::value(dc::Image::new("example/web:2.0").unwrap()));
+    ///
+    /// let (ovr, inexpressible) = dc::File::diff_as_override(&base, &target).unwrap();
+    /// assert!(inexpressible.is_empty());
+    /// assert!(ovr.services["web"].ports.is_empty());
+    /// assert_eq!(base.merge_override(&ovr), target);
+    /// ```
+    pub fn diff_as_override(
+        base: &File,
+        target: &File,
+    ) -> Result<(File, Vec<InexpressibleChange>)> {
+        diff_as_override(base, target)
+    }
+
+    /// Merge the changes made to `base` in `ours` with the changes made in
+    /// `theirs`.  Maps are merged by key.  Service fields which
+    /// `MergeOverride` merges item by item are merged the same way (so, for
+    /// example, `volumes` are identified by their container path), and
+    /// other lists, like `command`, are treated as single values.  If both
+    /// sides changed a field in different ways, the merged file uses our
+    /// version of it, and the field is returned as a `MergeConflict`.
+    pub fn merge_three_way(
+        base: &File,
+        ours: &File,
+        theirs: &File,
+    ) -> Result<(File, Vec<MergeConflict>)> {
+        merge_three_way(base, ours, theirs)
+    }
+
+    /// Convert this file to the specified version of the file format,
+    /// returning the converted file and a list of any fields which aren't
+    /// supported by that version.  `mode` controls what happens to those
+    /// fields.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use compose_yml::v2 as dc;
+    ///
+    /// let yaml = r#"---
+    /// version: "2.4"
+    /// services:
+    ///   web:
+    ///     image: "example/web"
+    ///     depends_on:
+    ///       db:
+    ///         condition: service_healthy
+    ///   db:
+    ///     image: "postgres"
+    /// "#;
+    /// let file = dc::File::from_str(yaml).unwrap();
+    ///
+    /// let (converted, report) =
+    ///     file.convert_to_version("2", dc::ConversionMode::Strip).unwrap();
+    /// assert_eq!(converted.version, "2");
+    /// assert_eq!(report[0].path.service.as_deref(), Some("web"));
+    /// assert_eq!(report[0].path.field, "depends_on");
+    /// assert_eq!(report[0].action, dc::IncompatibilityAction::Rewritten);
+    /// ```
+    pub fn convert_to_version(
+        &self,
+        version: &str,
+        mode: ConversionMode,
+    ) -> Result<(File, Vec<Incompatibility>)> {
+        let schema = schema_for_version(version)?;
+        let mut value =
+            serde_json::to_value(self).map_err(Error::validation_failed)?;
+        value["version"] = serde_json::Value::String(version.to_owned());
+
+        let unsupported = find_unsupported_fields(schema, &value);
+        let mut report = vec![];
+        // Work backwards, so that removing list items doesn't change the
+        // indices of fields we haven't handled yet.
+        for field in unsupported.iter().rev() {
+            let action = match mode {
+                ConversionMode::Check => IncompatibilityAction::Kept,
+                ConversionMode::Strip => strip_field(&mut value, field)?,
+            };
+            report.push(Incompatibility::new(&field.path, action));
+        }
+        report.reverse();
+
+        let converted: File =
+            serde_json::from_value(value).map_err(Error::validation_failed)?;
+        if mode == ConversionMode::Strip {
+            validate_file(&converted)?;
+        }
+        Ok((converted, report))
+    }
+}
+
+/// Rewrite or remove an unsupported field in a serialized file.
+fn strip_field(
+    value: &mut serde_json::Value,
+    field: &UnsupportedField,
+) -> Result<IncompatibilityAction> {
+    let cannot_strip = || {
+        let path: Vec<_> = field.path.iter().map(|e| e.to_string()).collect();
+        Error::DoesNotConformToSchema(format!(
+            "cannot remove unsupported field `{}`",
+            path.join(".")
+        ))
+    };
+    let (last, parent_path) = field.path.split_last().ok_or_else(cannot_strip)?;
+    let parent = parent_path
+        .iter()
+        .try_fold(value, |parent, elem| match elem {
+            PathElement::Key(key) => parent.get_mut(key.as_str()),
+            PathElement::Index(idx) => parent.get_mut(*idx),
+        })
+        .ok_or_else(cannot_strip)?;
+    match (last, parent) {
+        (PathElement::Key(key), serde_json::Value::Object(map)) => {
+            if field.accepts_key_list {
+                let keys = match map.get(key) {
+                    Some(serde_json::Value::Object(entries)) => entries
+                        .keys()
+                        .map(|k| serde_json::Value::String(k.to_owned()))
+                        .collect(),
+                    _ => return Err(cannot_strip()),
+                };
+                map.insert(key.to_owned(), serde_json::Value::Array(keys));
+                Ok(IncompatibilityAction::Rewritten)
+            } else {
+                map.remove(key);
+                Ok(IncompatibilityAction::Removed)
+            }
+        }
+        (PathElement::Index(idx), serde_json::Value::Array(items))
+            if *idx < items.len() =>
+        {
+            items.remove(*idx);
+            Ok(IncompatibilityAction::Removed)
+        }
+        _ => Err(cannot_strip()),
     }
 }
 
 impl Default for File {
     fn default() -> File {
         File {
-            version: "2.4".to_owned(),
+            version: version_string(LATEST_MINOR_VERSION),
             services: Default::default(),
             volumes: Default::default(),
             networks: Default::default(),
@@ -140,7 +600,7 @@ volumes:
 "#;
     assert_roundtrip!(File, yaml);
 
-    let file = File::from_str(&yaml).unwrap();
+    let file = File::from_str(yaml).unwrap();
     let foo = file.services.get("foo").unwrap();
     assert_eq!(
         foo.build.as_ref().unwrap().context,
@@ -176,7 +636,7 @@ fn file_allows_null_volumes_and_networks() {
   "bar":
   "foo":
 "#;
-    let file = File::from_str(&yaml).unwrap();
+    let file = File::from_str(yaml).unwrap();
     assert_eq!(file.volumes.len(), 2);
     assert_eq!(file.networks.len(), 2);
 }
@@ -189,7 +649,7 @@ fn file_checks_version_number() {
     "build": "."
 "version": "100"
 "#;
-    assert!(File::from_str(&yaml).is_err());
+    assert!(File::from_str(yaml).is_err());
 }
 
 // TODO: Disabled pending https://github.com/emk/compose_yml/issues/11
@@ -203,5 +663,315 @@ fn file_validates_against_schema() {
   "foo!":
     "build": "."
 "#;
-    assert!(File::from_str(&yaml).is_err());
+    assert!(File::from_str(yaml).is_err());
+}
+
+#[test]
+fn file_can_be_converted_to_older_version() {
+    let yaml = r#"---
+services:
+  db:
+    image: postgres
+  web:
+    build:
+      context: .
+      target: prod
+    depends_on:
+      db:
+        condition: service_healthy
+version: "2.4"
+"#;
+    let file = File::from_str(yaml).unwrap();
+
+    // Checking leaves the incompatible fields in place.
+    let (checked, report) =
+        file.convert_to_version("2", ConversionMode::Check).unwrap();
+    assert_eq!(checked.version, "2");
+    assert_eq!(checked.services, file.services);
+    let fields: Vec<_> = report.iter().map(|i| i.to_string()).collect();
+    assert_eq!(
+        fields,
+        vec!["services.web.build.target", "services.web.depends_on"]
+    );
+    assert!(report
+        .iter()
+        .all(|i| i.action == IncompatibilityAction::Kept));
+
+    // Stripping removes or rewrites them.
+    let (stripped, report) =
+        file.convert_to_version("2", ConversionMode::Strip).unwrap();
+    assert_eq!(report[0].action, IncompatibilityAction::Removed);
+    assert_eq!(report[1].action, IncompatibilityAction::Rewritten);
+    let web = &stripped.services["web"];
+    assert!(web.build.as_ref().unwrap().target.is_none());
+    assert_eq!(web.depends_on["db"], Dependency::default());
+
+    // Converting to a newer version finds nothing.
+    let (_, report) = file
+        .convert_to_version("2.4", ConversionMode::Strip)
+        .unwrap();
+    assert!(report.is_empty());
+}
+
+#[test]
+fn file_can_be_interpolated_for_several_environments() {
+    let yaml = r#"---
+services:
+  web:
+    image: "example/web:${TAG}"
+version: "2"
+"#;
+    let template = File::from_str(yaml).unwrap();
+    for tag in &["1.0", "2.0"] {
+        let mut env = BTreeMap::new();
+        env.insert("TAG".to_owned(), tag.to_string());
+        let mut file = template.clone();
+        file.make_standalone_env(Path::new("."), &env).unwrap();
+        let image = file.services["web"].image.as_ref().unwrap();
+        assert_eq!(
+            image.value().unwrap().to_string(),
+            format!("example/web:{}", tag)
+        );
+    }
+}
+
+#[test]
+fn files_can_be_read_with_overrides_from_other_directories() {
+    let dir = TestDir::new("read_all");
+    fs::create_dir_all(dir.join("overrides")).unwrap();
+    fs::write(
+        dir.join("docker-compose.yml"),
+        r#"---
+services:
+  web:
+    build: ./web
+    volumes:
+      - ./data:/data
+version: "2"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("overrides/dev.yml"),
+        r#"---
+services:
+  web:
+    env_file: ./dev.env
+    volumes:
+      - ../src:/src
+version: "2.1"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("docker-compose.override.yml"),
+        "---\nservices:\n  web:\n    dns: 8.8.8.8\nversion: \"2\"\n",
+    )
+    .unwrap();
+
+    let file = File::read_all(&[
+        dir.join("docker-compose.yml"),
+        dir.join("overrides/dev.yml"),
+    ])
+    .unwrap();
+    assert_eq!(file.version, "2.1");
+    let web = &file.services["web"];
+    assert_eq!(
+        web.build.as_ref().unwrap().context,
+        value(Context::new("./web"))
+    );
+    assert_eq!(
+        web.env_files,
+        vec![value(PathBuf::from("overrides/dev.env"))]
+    );
+    let volumes: Vec<_> = web.volumes.iter().map(|v| v.to_string()).collect();
+    assert_eq!(volumes, vec!["./data:/data", "./src:/src"]);
+
+    let file = File::read_with_override(dir.join("docker-compose.yml")).unwrap();
+    assert_eq!(file.version, "2");
+    assert_eq!(file.services["web"].dns.len(), 1);
+}
+
+#[test]
+fn read_all_requires_at_least_one_file() {
+    let paths: &[&str] = &[];
+    let err = File::read_all(paths).unwrap_err();
+    assert!(matches!(err, Error::NoFiles));
+    assert_eq!(err.to_string(), "at least one file is required");
+}
+
+#[test]
+fn read_all_reports_which_file_is_invalid() {
+    let dir = TestDir::new("read_all_invalid");
+    fs::write(
+        dir.join("docker-compose.yml"),
+        "---\nservices:\n  web:\n    image: \"example/web\"\nversion: \"2\"\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("docker-compose.dev.yml"),
+        "---\nservices:\n  web:\n    healthcheck:\n      interval: 30s\nversion: \"2\"\n",
+    )
+    .unwrap();
+
+    let err = File::read_all(&[
+        dir.join("docker-compose.yml"),
+        dir.join("docker-compose.dev.yml"),
+    ])
+    .unwrap_err();
+    match err {
+        Error::ReadFile { path, .. } => {
+            assert_eq!(path, dir.join("docker-compose.dev.yml"))
+        }
+        other => panic!("expected ReadFile, got {:?}", other),
+    }
+}
+
+#[test]
+fn read_all_keeps_flags_which_overrides_omit() {
+    let dir = TestDir::new("read_all_flags");
+    fs::write(
+        dir.join("docker-compose.yml"),
+        r#"---
+services:
+  web:
+    image: "example/web"
+    privileged: true
+    tty: true
+version: "2"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("docker-compose.dev.yml"),
+        "---\nservices:\n  web:\n    image: \"example/web:dev\"\nversion: \"2\"\n",
+    )
+    .unwrap();
+
+    let file = File::read_all(&[
+        dir.join("docker-compose.yml"),
+        dir.join("docker-compose.dev.yml"),
+    ])
+    .unwrap();
+    let web = &file.services["web"];
+    assert_eq!(web.privileged, Some(true));
+    assert_eq!(web.tty, Some(true));
+}
+
+#[test]
+fn overrides_can_reset_and_replace_values() {
+    let dir = TestDir::new("directives");
+    fs::write(
+        dir.join("docker-compose.yml"),
+        r#"---
+services:
+  web:
+    image: "example/web"
+    privileged: true
+    ports: ["80:80", "443:443"]
+    environment:
+      MODE: prod
+      LOG: info
+  worker:
+    image: "example/worker"
+version: "2"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("docker-compose.override.yml"),
+        r#"---
+services:
+  web:
+    privileged: !reset null
+    ports: !override
+      - "8080:80"
+    environment:
+      LOG: !reset
+      DEBUG: "1"
+  worker: !reset
+version: "2"
+"#,
+    )
+    .unwrap();
+
+    let file = File::read_with_override(dir.join("docker-compose.yml")).unwrap();
+    let expected = File::from_str(
+        r#"---
+services:
+  web:
+    image: "example/web"
+    ports: ["8080:80"]
+    environment:
+      MODE: prod
+      DEBUG: "1"
+version: "2"
+"#,
+    )
+    .unwrap();
+    assert_eq!(file, expected);
+}
+
+#[test]
+fn overrides_cannot_reset_list_items() {
+    let dir = TestDir::new("reset_list_item");
+    fs::write(
+        dir.join("docker-compose.yml"),
+        r#"---
+services:
+  web:
+    image: "example/web"
+    dns: ["1.1.1.1", "8.8.8.8"]
+version: "2"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("docker-compose.override.yml"),
+        "---\nservices:\n  web:\n    dns: [!reset \"8.8.8.8\"]\nversion: \"2\"\n",
+    )
+    .unwrap();
+
+    let err = File::read_with_override(dir.join("docker-compose.yml")).unwrap_err();
+    let source = match &err {
+        Error::ReadFile { source, .. } => source.downcast_ref::<Error>(),
+        _ => None,
+    };
+    match source {
+        Some(Error::ResetListItem { path }) => assert_eq!(path, "services.web.dns.0"),
+        _ => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[test]
+fn overrides_cannot_override_list_items() {
+    let dir = TestDir::new("override_list_item");
+    fs::write(
+        dir.join("docker-compose.yml"),
+        r#"---
+services:
+  web:
+    image: "example/web"
+    dns: ["1.1.1.1", "8.8.8.8"]
+version: "2"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("docker-compose.override.yml"),
+        "---\nservices:\n  web:\n    dns: [\"9.9.9.9\", !override \"8.8.4.4\"]\nversion: \"2\"\n",
+    )
+    .unwrap();
+
+    let err = File::read_with_override(dir.join("docker-compose.yml")).unwrap_err();
+    let source = match &err {
+        Error::ReadFile { source, .. } => source.downcast_ref::<Error>(),
+        _ => None,
+    };
+    match source {
+        Some(Error::OverrideListItem { path }) => {
+            assert_eq!(path, "services.web.dns.1")
+        }
+        _ => panic!("unexpected error: {:?}", err),
+    }
 }
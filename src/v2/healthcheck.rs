@@ -0,0 +1,60 @@
+use super::common::*;
+
+/// How to check whether a service's containers are healthy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Healthcheck {
+    /// The command to run.  Either shell code, or a list starting with
+    /// `NONE`, `CMD` or `CMD-SHELL`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test: Option<CommandLine>,
+
+    /// How often to run the check.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<RawOr<String>>,
+
+    /// How long to wait for the check to finish.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<RawOr<String>>,
+
+    /// How many consecutive failures are needed to mark the container as
+    /// unhealthy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<RawOr<u32>>,
+
+    /// How long to give the container to start up before failures are
+    /// counted.  Requires version 2.3 or 3.4.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_period: Option<RawOr<String>>,
+
+    /// Disable any healthcheck specified by the image.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub disable: bool,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(Healthcheck, {
+    test, interval, timeout, retries, start_period, disable, _hidden
+});
+
+#[test]
+fn healthcheck_can_be_roundtripped() {
+    let yaml = r#"---
+interval: 1m30s
+retries: 3
+test:
+  - CMD
+  - curl
+  - "-f"
+  - "http://localhost"
+timeout: 10s
+"#;
+    assert_roundtrip!(Healthcheck, yaml);
+}
@@ -6,6 +6,7 @@ use regex::Regex;
 use serde::de::{
     self, Deserialize, DeserializeOwned, Deserializer, MapAccess, SeqAccess, Visitor,
 };
+use serde::{Serialize, Serializer};
 use std::collections::BTreeMap;
 use std::fmt;
 use std::marker::PhantomData;
@@ -216,6 +217,25 @@ where
     deserializer.deserialize_any(MapOrDefaultListVisitor(PhantomData::<T>))
 }
 
+/// The inverse of `deserialize_map_or_default_list`: if every value in the
+/// map is `Default::default()`, serialize it as a list of keys.  Otherwise,
+/// serialize it as a map.
+pub fn serialize_map_or_default_list<T, S>(
+    map: &BTreeMap<String, T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    T: Default + PartialEq + Serialize,
+    S: Serializer,
+{
+    let default = T::default();
+    if map.values().all(|v| *v == default) {
+        serializer.collect_seq(map.keys())
+    } else {
+        map.serialize(serializer)
+    }
+}
+
 /// Deserialize either list or a single bare string as a list.
 pub fn deserialize_item_or_list<'de, T, D>(
     deserializer: D,
@@ -265,7 +285,7 @@ where
         }
     }
 
-    deserializer.deserialize_seq(StringOrListVisitor(PhantomData))
+    deserializer.deserialize_any(StringOrListVisitor(PhantomData))
 }
 
 /// Deserialize either list or a single bare string as a list.
@@ -21,6 +21,12 @@ impl HostMapping {
 
 impl_interpolatable_value!(HostMapping);
 
+/// Identify a host mapping by its hostname, if we know it.  When merging,
+/// a mapping in an override replaces any mapping with the same hostname.
+pub(crate) fn host_mapping_key(mapping: &RawOr<HostMapping>) -> Option<String> {
+    mapping.value().ok().map(|m| m.hostname.clone())
+}
+
 impl fmt::Display for HostMapping {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}:{}", &self.hostname, &self.address)
@@ -0,0 +1,62 @@
+use super::common::*;
+
+/// What should `File::convert_to_version` do with fields which aren't
+/// supported by the target version?
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionMode {
+    /// Leave incompatible fields in place, and just report them.  The
+    /// converted file may not pass validation.
+    Check,
+    /// Rewrite incompatible fields to a simpler form where possible (for
+    /// example, dropping `depends_on` conditions), and remove them
+    /// otherwise.
+    Strip,
+}
+
+/// What `File::convert_to_version` did with an incompatible field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IncompatibilityAction {
+    /// The field was left unchanged.
+    Kept,
+    /// The field was removed.
+    Removed,
+    /// The field was rewritten to a form supported by the target version,
+    /// which may have lost some information.
+    Rewritten,
+}
+
+/// A field which isn't supported by the target version of a
+/// `File::convert_to_version` call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Incompatibility {
+    /// The unsupported field.
+    pub path: FieldPath,
+
+    /// What we did with this field.
+    pub action: IncompatibilityAction,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    pub _hidden: (),
+}
+
+impl Incompatibility {
+    /// Describe the field at `path`, which is relative to the top of the
+    /// file.
+    pub(crate) fn new(path: &[PathElement], action: IncompatibilityAction) -> Self {
+        Incompatibility {
+            path: FieldPath::new(path),
+            action,
+            _hidden: (),
+        }
+    }
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path)
+    }
+}
@@ -0,0 +1,225 @@
+//! Sources of environment variables for interpolation.
+
+use log::trace;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fmt;
+use std::hash::BuildHasher;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::result;
+
+use super::super::env_file::EnvFile;
+use super::provenance::VariableSource;
+use crate::errors::*;
+
+/// A source of environment variable values.
+pub trait Environment {
+    /// Fetch a variable from this environment.  Similar to
+    /// `std::env::var`.
+    fn var(&self, key: &str) -> result::Result<String, env::VarError>;
+
+    /// Describe where `var` finds the value of `key`, for use in
+    /// `InterpolationRecord`s.  The default implementation returns
+    /// `VariableSource::Other`.
+    fn source(&self, _key: &str) -> VariableSource {
+        VariableSource::Other
+    }
+}
+
+impl<E: Environment + ?Sized> Environment for &E {
+    fn var(&self, key: &str) -> result::Result<String, env::VarError> {
+        (**self).var(key)
+    }
+
+    fn source(&self, key: &str) -> VariableSource {
+        (**self).source(key)
+    }
+}
+
+/// Look up variables in a map, which is handy for tests and for
+/// interpolating a file deterministically.
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use compose_yml::v2 as dc;
+/// use compose_yml::v2::Environment;
+///
+/// let mut env = BTreeMap::new();
+/// env.insert("TAG".to_owned(), "1.0".to_owned());
+/// assert_eq!(env.var("TAG").unwrap(), "1.0");
+/// assert!(env.var("NOSUCH").is_err());
+/// ```
+impl Environment for BTreeMap<String, String> {
+    fn var(&self, key: &str) -> result::Result<String, env::VarError> {
+        self.get(key).cloned().ok_or(env::VarError::NotPresent)
+    }
+}
+
+impl<S: BuildHasher> Environment for HashMap<String, String, S> {
+    fn var(&self, key: &str) -> result::Result<String, env::VarError> {
+        self.get(key).cloned().ok_or(env::VarError::NotPresent)
+    }
+}
+
+/// Fetches environment variables from `std::env`.
+#[derive(Debug, Default)]
+#[allow(missing_copy_implementations)]
+pub struct OsEnvironment {
+    /// A placeholder to prevent this struct from being directly
+    /// constructed.
+    _hidden: (),
+}
+
+impl OsEnvironment {
+    /// Create a new `OsEnvironment`.
+    pub fn new() -> OsEnvironment {
+        Default::default()
+    }
+}
+
+impl Environment for OsEnvironment {
+    fn var(&self, key: &str) -> result::Result<String, env::VarError> {
+        let result = env::var(key);
+        trace!("Read env var {}: {:?}", key, &result);
+        result
+    }
+
+    fn source(&self, _key: &str) -> VariableSource {
+        VariableSource::Os
+    }
+}
+
+/// Fetches environment variables from a `.env` file, like the one that
+/// `docker-compose` loads from the project directory.
+#[derive(Debug)]
+pub struct DotEnvEnvironment {
+    /// The variables we loaded.
+    env_file: EnvFile,
+    /// The path we loaded them from, if any.
+    path: Option<PathBuf>,
+}
+
+impl DotEnvEnvironment {
+    /// Read a `.env` file from a stream.
+    pub fn read<R: io::Read>(input: R) -> Result<DotEnvEnvironment> {
+        Ok(DotEnvEnvironment {
+            env_file: EnvFile::read(input)?,
+            path: None,
+        })
+    }
+
+    /// Load a `.env` file from the specified path.
+    pub fn load(path: &Path) -> Result<DotEnvEnvironment> {
+        Ok(DotEnvEnvironment {
+            env_file: EnvFile::load(path)?,
+            path: Some(path.to_owned()),
+        })
+    }
+
+    /// Load the `.env` file in the project directory `dir`.  As with
+    /// `docker-compose`, a missing `.env` file is treated as empty.
+    pub fn load_project(dir: &Path) -> Result<DotEnvEnvironment> {
+        let path = dir.join(".env");
+        if path.exists() {
+            Self::load(&path)
+        } else {
+            Self::read(io::empty())
+        }
+    }
+}
+
+impl Environment for DotEnvEnvironment {
+    fn var(&self, key: &str) -> result::Result<String, env::VarError> {
+        self.env_file
+            .var(key)
+            .map(|val| val.to_owned())
+            .ok_or(env::VarError::NotPresent)
+    }
+
+    fn source(&self, _key: &str) -> VariableSource {
+        VariableSource::DotEnv(self.path.clone())
+    }
+}
+
+/// Consults several environments in priority order, and returns the
+/// first value found.
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use std::io;
+/// use compose_yml::v2 as dc;
+/// use compose_yml::v2::Environment;
+///
+/// let mut overrides = BTreeMap::new();
+/// overrides.insert("TAG".to_owned(), "dev".to_owned());
+/// let dot_env = dc::DotEnvEnvironment::read(io::Cursor::new(
+///     "TAG=latest\nPORT=8080\n",
+/// )).unwrap();
+///
+/// let mut env = dc::LayeredEnvironment::new();
+/// env.push(overrides);
+/// env.push(dot_env);
+/// assert_eq!(env.var("TAG").unwrap(), "dev");
+/// assert_eq!(env.var("PORT").unwrap(), "8080");
+/// assert!(env.var("NOSUCH").is_err());
+/// ```
+#[derive(Default)]
+pub struct LayeredEnvironment<'a> {
+    /// Our layers, highest priority first.
+    layers: Vec<Box<dyn Environment + 'a>>,
+}
+
+impl<'a> LayeredEnvironment<'a> {
+    /// Create a new `LayeredEnvironment` with no layers.
+    pub fn new() -> LayeredEnvironment<'a> {
+        Default::default()
+    }
+
+    /// Add a new layer, which has a lower priority than any existing
+    /// layers.
+    pub fn push<E: Environment + 'a>(&mut self, env: E) {
+        self.layers.push(Box::new(env));
+    }
+}
+
+impl<'a> fmt::Debug for LayeredEnvironment<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LayeredEnvironment({} layers)", self.layers.len())
+    }
+}
+
+impl<'a> Environment for LayeredEnvironment<'a> {
+    fn var(&self, key: &str) -> result::Result<String, env::VarError> {
+        for layer in &self.layers {
+            if let Ok(val) = layer.var(key) {
+                return Ok(val);
+            }
+        }
+        Err(env::VarError::NotPresent)
+    }
+
+    fn source(&self, key: &str) -> VariableSource {
+        self.layers
+            .iter()
+            .find(|layer| layer.var(key).is_ok())
+            .map(|layer| layer.source(key))
+            .unwrap_or(VariableSource::Other)
+    }
+}
+
+#[test]
+fn layered_environment_can_borrow_layers() {
+    let mut base = HashMap::new();
+    base.insert("FOO".to_owned(), "base".to_owned());
+    base.insert("BAR".to_owned(), "base".to_owned());
+    let mut top = BTreeMap::new();
+    top.insert("FOO".to_owned(), "top".to_owned());
+
+    let mut env = LayeredEnvironment::new();
+    env.push(&top);
+    env.push(&base);
+    assert_eq!(env.var("FOO").unwrap(), "top");
+    assert_eq!(env.var("BAR").unwrap(), "base");
+    assert_eq!(base.len(), 2);
+}
@@ -0,0 +1,1478 @@
+//! Interpolation of shell-style variables into strings.
+
+use log::warn;
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+use std::collections::BTreeMap;
+#[cfg(test)]
+use std::env;
+use std::error;
+use std::fmt::{self, Display};
+use std::marker::PhantomData;
+use std::num::ParseIntError;
+use std::path::{Path, PathBuf};
+use std::result;
+use std::str::FromStr;
+use std::string;
+use void::Void;
+
+pub use self::environment::{
+    DotEnvEnvironment, Environment, LayeredEnvironment, OsEnvironment,
+};
+use self::parser::{parse, Expansion, Operator, Segment};
+pub use self::provenance::{InterpolationRecord, VariableSource, VariableUse};
+pub(crate) use self::redaction::with_redaction;
+pub use self::redaction::SensitiveVariables;
+use self::redaction::{is_redacting, Redaction, REDACTED_PLACEHOLDER};
+pub use self::variable_reference::VariableReference;
+use super::merge_override::MergeOverride;
+use crate::errors::*;
+
+mod environment;
+mod parser;
+mod provenance;
+mod redaction;
+mod variable_reference;
+
+/// What to do when interpolation encounters an undefined variable with no
+/// default value, as in `$FOO` or `${FOO}`.
+#[derive(Clone, Copy, Default)]
+pub enum UndefinedVariable<'a> {
+    /// Fail with `Error::InterpolateUndefinedVariable`.  This is the
+    /// default, and it's stricter than `docker-compose`.
+    #[default]
+    Error,
+    /// Substitute an empty string and log a warning, the same way that
+    /// `docker-compose` does.
+    Empty,
+    /// Leave the variable reference in place.  When interpolating a
+    /// `RawOr` value using `InterpolateAll`, the entire value is left
+    /// uninterpolated.
+    LeaveUnexpanded,
+    /// Call the supplied function with the name of the variable, and
+    /// substitute whatever it returns.
+    Callback(&'a dyn Fn(&str) -> Result<String>),
+}
+
+impl<'a> fmt::Debug for UndefinedVariable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            UndefinedVariable::Error => write!(f, "Error"),
+            UndefinedVariable::Empty => write!(f, "Empty"),
+            UndefinedVariable::LeaveUnexpanded => write!(f, "LeaveUnexpanded"),
+            UndefinedVariable::Callback(_) => write!(f, "Callback(..)"),
+        }
+    }
+}
+
+/// Options controlling how we interpolate environment variables.
+///
+/// ```
+/// use std::env;
+/// use compose_yml::v2 as dc;
+/// use compose_yml::v2::InterpolateAll;
+///
+/// env::remove_var("NO_SUCH_TAG");
+/// let mut image: dc::RawOr<dc::Image> = dc::raw("example:${NO_SUCH_TAG}").unwrap();
+///
+/// let mut opts = dc::InterpolateOptions::new();
+/// opts.undefined = dc::UndefinedVariable::Callback(&|_name| Ok("latest".to_owned()));
+/// image.interpolate_all_with(&opts).unwrap();
+/// assert_eq!(image.to_string(), "example:latest");
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct InterpolateOptions<'a> {
+    /// What to do with undefined variables.
+    pub undefined: UndefinedVariable<'a>,
+
+    /// Variables whose values should be hidden when displaying
+    /// interpolated values.  See `RawOr::redacted`.
+    pub sensitive: SensitiveVariables,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    pub _hidden: (),
+}
+
+impl<'a> InterpolateOptions<'a> {
+    /// Create a new `InterpolateOptions` with the default settings.
+    pub fn new() -> InterpolateOptions<'a> {
+        Default::default()
+    }
+}
+
+/// Different modes in which we can run `interpolation_helper`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Interpolate environment variables.
+    Interpolate,
+    /// Unescape an interpolation string if possible, but fail if we would
+    /// need to interpolate a value.
+    Unescape,
+    /// Validate an interpolation string.
+    Validate,
+    /// Interpolate the variables which are defined, and leave references
+    /// to any other variables in place, producing a new interpolation
+    /// string.
+    Partial,
+}
+
+/// An internal function which handles interpolating, unescaping and
+/// validating interpolation strings.  We use a single function for all
+/// of these to prevent the risk of divergent code paths.
+fn interpolate_helper(
+    input: &str,
+    mode: Mode,
+    env: &dyn Environment,
+    options: &InterpolateOptions<'_>,
+) -> Result<Interpolated> {
+    let segments = parse(input)?;
+    match mode {
+        // Parsing succeeded, so the syntax is valid.
+        Mode::Validate => Ok(Interpolated::default()),
+        // We can only unescape strings which contain no expansions.
+        Mode::Unescape => {
+            let mut result = Interpolated::default();
+            for segment in &segments {
+                match segment {
+                    Segment::Literal(text) => result.text.push_str(text),
+                    Segment::Expansion(_) => {
+                        return Err(Error::InterpolationDisabled(input.to_owned()))
+                    }
+                }
+            }
+            Ok(result)
+        }
+        Mode::Interpolate | Mode::Partial => {
+            let mut interpolator = Interpolator {
+                env,
+                options,
+                partial: mode == Mode::Partial,
+                result: Interpolated::default(),
+            };
+            interpolator.interpolate_segments(&segments)?;
+            Ok(interpolator.result)
+        }
+    }
+}
+
+/// The output of `interpolate_helper`.
+#[derive(Debug, Default)]
+struct Interpolated {
+    /// The interpolated text.
+    text: String,
+    /// The interpolated text, with the values of sensitive variables
+    /// replaced by a placeholder.
+    redacted: String,
+    /// Did we use any sensitive variables?
+    used_sensitive: bool,
+    /// Did we leave any undefined variables unexpanded?
+    unexpanded: bool,
+    /// The variables we used.
+    variables: Vec<VariableUse>,
+}
+
+/// Interpolates parsed segments, looking up variables in an environment.
+struct Interpolator<'a> {
+    /// Where to look up variables.
+    env: &'a dyn Environment,
+    /// How to handle undefined variables.
+    options: &'a InterpolateOptions<'a>,
+    /// Should we produce a new interpolation string, leaving references
+    /// to undefined variables in place?
+    partial: bool,
+    /// The output we've accumulated so far.
+    result: Interpolated,
+}
+
+impl<'a> Interpolator<'a> {
+    /// Interpolate `segments` and append them to our result.
+    fn interpolate_segments(&mut self, segments: &[Segment]) -> Result<()> {
+        for segment in segments {
+            match segment {
+                Segment::Literal(text) => self.push_text(text),
+                Segment::Expansion(exp) => self.expand(exp)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Append literal text to our result, escaping it if we're producing
+    /// an interpolation string.
+    fn push_text(&mut self, text: &str) {
+        if self.partial {
+            self.push_raw(&escape_str(text));
+        } else {
+            self.push_raw(text);
+        }
+    }
+
+    /// Append text to our result exactly as given.
+    fn push_raw(&mut self, text: &str) {
+        self.result.text.push_str(text);
+        self.result.redacted.push_str(text);
+    }
+
+    /// Append the value of a variable to our result, hiding it in our
+    /// redacted output if it's sensitive.
+    fn push_value(&mut self, name: &str, val: &str) {
+        if self.options.sensitive.is_sensitive(name) {
+            let start = self.result.redacted.len();
+            self.push_text(val);
+            self.result.redacted.truncate(start);
+            self.result.redacted.push_str(REDACTED_PLACEHOLDER);
+            self.result.used_sensitive = true;
+        } else {
+            self.push_text(val);
+        }
+    }
+
+    /// Interpolate `segments` and return them as a string, without
+    /// appending them to our result.
+    fn interpolate_to_string(&mut self, segments: &[Segment]) -> Result<String> {
+        let start = self.result.text.len();
+        let redacted_start = self.result.redacted.len();
+        self.interpolate_segments(segments)?;
+        self.result.redacted.truncate(redacted_start);
+        Ok(self.result.text.split_off(start))
+    }
+
+    /// Expand a single variable.  The argument of `exp` is only
+    /// interpolated if we actually need it, so `${FOO:-$BAR}` doesn't
+    /// require `BAR` to be set when `FOO` is.
+    fn expand(&mut self, exp: &Expansion) -> Result<()> {
+        // The `:` forms treat set-but-empty variables as if they were
+        // unset.
+        let val = match self.env.var(&exp.name) {
+            // In partial mode, we leave references to undefined variables
+            // exactly as we found them, including any operators.
+            Err(_) if self.partial => {
+                self.push_raw(&exp.source);
+                self.result.unexpanded = true;
+                return Ok(());
+            }
+            Ok(ref val) if val.is_empty() && exp.colon => None,
+            Ok(val) => Some(val),
+            Err(_) => None,
+        };
+        let source = match (exp.operator, &val) {
+            (_, Some(_)) => self.env.source(&exp.name),
+            (Some(Operator::Default), None) => VariableSource::Default,
+            (_, None) => VariableSource::Undefined,
+        };
+        self.result
+            .variables
+            .push(VariableUse::new(&exp.name, source));
+
+        match (exp.operator, val) {
+            // `${FOO+alternate}` uses the alternate if `FOO` is set.
+            (Some(Operator::Alternate), Some(_)) => {
+                self.interpolate_segments(&exp.arg)?
+            }
+            (Some(Operator::Alternate), None) => {}
+            // A set environment variable.
+            (_, Some(val)) => self.push_value(&exp.name, &val),
+            // An unset environment variable with a default value.
+            (Some(Operator::Default), None) => self.interpolate_segments(&exp.arg)?,
+            // An unset environment variable which is required.
+            (Some(Operator::Required), None) => {
+                return Err(Error::InterpolateRequiredVariable {
+                    name: exp.name.clone(),
+                    message: self.interpolate_to_string(&exp.arg)?,
+                })
+            }
+            // An unset environment variable with no default provided.
+            (None, None) => match self.options.undefined {
+                UndefinedVariable::Error => {
+                    return Err(Error::InterpolateUndefinedVariable(exp.name.clone()))
+                }
+                UndefinedVariable::Empty => warn!(
+                    "The {} variable is not set, substituting an empty string",
+                    exp.name
+                ),
+                UndefinedVariable::LeaveUnexpanded => {
+                    self.push_raw(&exp.source);
+                    self.result.unexpanded = true;
+                }
+                UndefinedVariable::Callback(callback) => {
+                    self.push_text(&callback(&exp.name)?)
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Interpolate environment variables into a string using the same rules as
+/// `docker-compose.yml`.
+fn interpolate_env(input: &str, env: &dyn Environment) -> Result<String> {
+    interpolate_env_with(input, env, &InterpolateOptions::default())
+}
+
+/// Interpolate environment variables into a string, using `options` to
+/// decide how to handle undefined variables.
+fn interpolate_env_with(
+    input: &str,
+    env: &dyn Environment,
+    options: &InterpolateOptions<'_>,
+) -> Result<String> {
+    Ok(interpolate_helper(input, Mode::Interpolate, env, options)?.text)
+}
+
+#[test]
+fn interpolate_env_interpolates_env_vars() {
+    env::set_var("FOO", "foo");
+    env::remove_var("BAR");
+    env::set_var("EMPTY", "");
+    let env = OsEnvironment::new();
+
+    assert_eq!("foo", interpolate_env("$FOO", &env).unwrap());
+    assert_eq!("foo", interpolate_env("${FOO}", &env).unwrap());
+    assert_eq!("foo foo", interpolate_env("$FOO $FOO", &env).unwrap());
+    assert_eq!("plain", interpolate_env("plain", &env).unwrap());
+    assert_eq!("$escaped", interpolate_env("$$escaped", &env).unwrap());
+    assert_eq!("${escaped}", interpolate_env("$${escaped}", &env).unwrap());
+
+    // Defaulting.
+    assert_eq!("bar", interpolate_env("${BAR-bar}", &env).unwrap());
+    assert_eq!("bar", interpolate_env("${BAR:-bar}", &env).unwrap());
+    assert_eq!("", interpolate_env("${EMPTY-bar}", &env).unwrap());
+    assert_eq!("bar", interpolate_env("${EMPTY:-bar}", &env).unwrap());
+
+    // Alternate values.
+    assert_eq!("alt", interpolate_env("${FOO+alt}", &env).unwrap());
+    assert_eq!("alt", interpolate_env("${FOO:+alt}", &env).unwrap());
+    assert_eq!("alt", interpolate_env("${EMPTY+alt}", &env).unwrap());
+    assert_eq!("", interpolate_env("${EMPTY:+alt}", &env).unwrap());
+    assert_eq!("", interpolate_env("${BAR+alt}", &env).unwrap());
+    assert_eq!("", interpolate_env("${BAR:+alt}", &env).unwrap());
+
+    // Required variables.
+    assert_eq!("foo", interpolate_env("${FOO?err}", &env).unwrap());
+    assert_eq!("foo", interpolate_env("${FOO:?err}", &env).unwrap());
+    assert_eq!("", interpolate_env("${EMPTY?err}", &env).unwrap());
+}
+
+#[test]
+fn interpolate_env_handles_short_names_and_nested_defaults() {
+    env::set_var("X", "x");
+    env::set_var("FOO", "foo");
+    env::remove_var("BAR");
+    env::remove_var("BAZ");
+    let env = OsEnvironment::new();
+
+    assert_eq!("x", interpolate_env("$X", &env).unwrap());
+    assert_eq!("x-y", interpolate_env("${X}-y", &env).unwrap());
+    assert_eq!("foo", interpolate_env("${BAR:-${FOO}}", &env).unwrap());
+    assert_eq!(
+        "x/foo",
+        interpolate_env("${BAR-$X/${BAZ:-$FOO}}", &env).unwrap()
+    );
+    assert_eq!("$x", interpolate_env("${BAR:-$$$X}", &env).unwrap());
+
+    // Defaults are only evaluated when they're needed.
+    assert_eq!("foo", interpolate_env("${FOO:-${BAZ}}", &env).unwrap());
+    assert!(interpolate_env("${BAR:-${BAZ}}", &env).is_err());
+}
+
+#[test]
+fn interpolate_env_reports_missing_required_variables() {
+    env::remove_var("BAR");
+    env::set_var("EMPTY", "");
+    let env = OsEnvironment::new();
+
+    for input in &["${BAR?bar is required}", "${BAR:?bar is required}"] {
+        match interpolate_env(input, &env) {
+            Err(Error::InterpolateRequiredVariable { name, message }) => {
+                assert_eq!(name, "BAR");
+                assert_eq!(message, "bar is required");
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+    assert!(interpolate_env("${EMPTY:?must not be empty}", &env).is_err());
+}
+
+#[test]
+fn interpolate_env_returns_an_error_if_input_is_invalid() {
+    let env = OsEnvironment::new();
+
+    // See https://github.com/docker/compose/blob/master/
+    // tests/unit/interpolation_test.py
+    assert!(interpolate_env("$", &env).is_err());
+    assert!(interpolate_env("${", &env).is_err());
+    assert!(interpolate_env("$}", &env).is_err());
+    assert!(interpolate_env("${}", &env).is_err());
+    assert!(interpolate_env("${ }", &env).is_err());
+    assert!(interpolate_env("${ foo}", &env).is_err());
+    assert!(interpolate_env("${foo }", &env).is_err());
+    assert!(interpolate_env("${foo!}", &env).is_err());
+}
+
+#[test]
+fn interpolate_env_returns_an_error_if_variable_is_undefined() {
+    let env = OsEnvironment::new();
+
+    // This behavior differs from `docker-compose`, which treats undefined
+    // env variables as empty strings.
+    env::remove_var("NOSUCH");
+    assert!(interpolate_env("$NOSUCH", &env).is_err());
+}
+
+#[test]
+fn interpolate_env_with_applies_undefined_variable_policy() {
+    env::set_var("FOO", "foo");
+    env::remove_var("NOSUCH");
+    let env = OsEnvironment::new();
+    let mut opts = InterpolateOptions::new();
+
+    assert!(interpolate_env_with("$FOO $NOSUCH", &env, &opts).is_err());
+
+    opts.undefined = UndefinedVariable::Empty;
+    let result = interpolate_env_with("$FOO $NOSUCH", &env, &opts);
+    assert_eq!("foo ", result.unwrap());
+
+    opts.undefined = UndefinedVariable::LeaveUnexpanded;
+    let result = interpolate_env_with("$FOO ${NOSUCH}", &env, &opts);
+    assert_eq!("foo ${NOSUCH}", result.unwrap());
+
+    let callback = |name: &str| Ok(name.to_lowercase());
+    opts.undefined = UndefinedVariable::Callback(&callback);
+    let result = interpolate_env_with("$FOO $NOSUCH", &env, &opts);
+    assert_eq!("foo nosuch", result.unwrap());
+
+    // Default values and required variables aren't affected.
+    let result = interpolate_env_with("${NOSUCH:-bar}", &env, &opts);
+    assert_eq!("bar", result.unwrap());
+    assert!(interpolate_env_with("${NOSUCH?}", &env, &opts).is_err());
+}
+
+#[test]
+fn interpolate_all_with_leaves_unexpanded_values_raw() {
+    env::set_var("FOO", "foo");
+    env::remove_var("NOSUCH");
+    let mut opts = InterpolateOptions::new();
+    opts.undefined = UndefinedVariable::LeaveUnexpanded;
+
+    let mut values: Vec<RawOr<String>> =
+        vec![raw("$FOO").unwrap(), raw("$FOO-$NOSUCH").unwrap()];
+    values.interpolate_all_with(&opts).unwrap();
+    assert_eq!(values[0].value().unwrap(), "foo");
+    assert_eq!(values[1].to_string(), "$FOO-$NOSUCH");
+}
+
+#[test]
+fn partial_interpolation_preserves_unknown_references() {
+    let mut env = BTreeMap::new();
+    env.insert("TAG".to_owned(), "v$1".to_owned());
+    env.insert("EMPTY".to_owned(), "".to_owned());
+    let partial = |input: &str| {
+        interpolate_helper(input, Mode::Partial, &env, &InterpolateOptions::default())
+            .unwrap()
+            .text
+    };
+
+    assert_eq!("v$$1", partial("$TAG"));
+    assert_eq!("$$x-v$$1-$X", partial("$$x-${TAG}-$X"));
+    assert_eq!("${X:-$$TAG}", partial("${X:-$$TAG}"));
+    assert_eq!("$X", partial("${EMPTY:-$X}"));
+    assert_eq!("v$$1", partial("${EMPTY:-${TAG}}"));
+    assert_eq!("alt", partial("${TAG:+alt}"));
+
+    // Fully interpolated values are parsed.
+    let mut value: RawOr<String> = raw("${TAG}!").unwrap();
+    value.interpolate_partial(&env).unwrap();
+    assert_eq!(value.value().unwrap(), "v$1!");
+}
+
+/// Escape interpolation sequences in a string literal.
+fn escape_str(input: &str) -> String {
+    input.replace("$", "$$")
+}
+
+#[test]
+fn escape_str_escapes_dollar_signs() {
+    assert_eq!("$$VAR1 $${VAR2} $$", escape_str("$VAR1 ${VAR2} $"));
+}
+
+/// Unescape any `$$` sequences to `$` in an interpolation string, but fail
+/// with an error if we encounter an actual interpolation that would
+/// require an environment variable.  This is used for manipulating
+/// `docker-compose.yml` files without expanding any environment variables.
+fn unescape_str(input: &str) -> Result<String> {
+    // We can use any `env` we want here; it will be ignored.
+    let env = OsEnvironment::new();
+    interpolate_helper(input, Mode::Unescape, &env, &InterpolateOptions::default())
+        .map(|interpolated| interpolated.text)
+}
+
+#[test]
+fn unescape_str_unescapes_without_interpolating() {
+    env::set_var("FOO", "foo");
+
+    // Actual interpolation is forbidden.
+    assert!(unescape_str("$FOO").is_err());
+    assert!(unescape_str("${FOO?err}").is_err());
+    assert!(unescape_str("${FOO:+alt}").is_err());
+
+    assert_eq!("plain", unescape_str("plain").unwrap());
+    assert_eq!("$escaped", unescape_str("$$escaped").unwrap());
+    assert_eq!("${escaped}", unescape_str("$${escaped}").unwrap());
+}
+
+/// Validate an interpolation string, making sure all interpolations look
+/// syntactically valid.
+fn validate(input: &str) -> Result<()> {
+    // We can use any `env` we want here; it will be ignored.
+    let env = OsEnvironment::new();
+    interpolate_helper(input, Mode::Validate, &env, &InterpolateOptions::default())
+        .map(|_| ())
+}
+
+#[test]
+fn validate_tests_interpolation_strings() {
+    assert!(validate("plain").is_ok());
+    assert!(validate("$$escaped").is_ok());
+    assert!(validate("$${escaped}").is_ok());
+    assert!(validate("$FOO").is_ok());
+    assert!(validate("${FOO}").is_ok());
+    assert!(validate("${FOO-default}").is_ok());
+    assert!(validate("${FOO:?error message}").is_ok());
+    assert!(validate("${FOO?}").is_ok());
+    assert!(validate("${FOO:+alternate}").is_ok());
+    assert!(validate("${FOO+alternate}").is_ok());
+
+    // See https://github.com/docker/compose/blob/master/
+    // tests/unit/interpolation_test.py
+    assert!(validate("${").is_err());
+    assert!(validate("$}").is_err());
+    assert!(validate("${}").is_err());
+    assert!(validate("${ }").is_err());
+    assert!(validate("${ foo}").is_err());
+    assert!(validate("${foo }").is_err());
+    assert!(validate("${foo!}").is_err());
+    assert!(validate("${foo:}").is_err());
+    assert!(validate("${foo:=bar}").is_err());
+}
+
+/// Local helper trait to convert the different kinds of errors we might
+/// receive from `FromStr::Err` into an `Error`.  Yeah, this is
+/// some abusive template metaprogramming, basically, even though we're not
+/// writing C++.
+///
+/// This will show up as an instance method on all affected types.
+pub trait IntoInvalidValueError: error::Error + Sized {
+    /// Consume an `Error` and return an `InvalidValueError`.  This is the
+    /// default implementation for when an `impl` doesn't override it with
+    /// something more specific.
+    fn into_invalid_value_error(self, wanted: &str, input: &str) -> Error {
+        Error::invalid_value(wanted, input)
+    }
+}
+
+impl IntoInvalidValueError for Error {
+    /// We already have the correct type of error, so we override this
+    /// function to copy it through.
+    fn into_invalid_value_error(self, _: &str, _: &str) -> Error {
+        self
+    }
+}
+
+impl IntoInvalidValueError for string::ParseError {
+    // Just use the default method in this case.
+}
+
+impl IntoInvalidValueError for Void {
+    fn into_invalid_value_error(self, _: &str, _: &str) -> Error {
+        unreachable!()
+    }
+}
+
+/// A value which can be represented as a string containing environment
+/// variable interpolations.  We require a custom `parse` implementation,
+/// because we want to handle types that are not necessarily `FromStr`.
+pub trait InterpolatableValue: Clone + Eq {
+    /// Our equivalent of `from_str`.
+    fn iv_from_str(s: &str) -> Result<Self>;
+    /// Our equivalent of `fmt`.
+    fn fmt_iv(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    /// Serialize a parsed value.  By default, we serialize values as
+    /// strings, escaping any `$` characters.
+    fn serialize_iv<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let s = format!("{}", DisplayInterpolatableValue(self));
+        serializer.serialize_str(&escape_str(&s))
+    }
+
+    /// Deserialize a `RawOr` containing this type.  By default, we only
+    /// accept strings.  Unquoted YAML scalars like `1.10` or `true` are
+    /// read as strings, too, exactly as they were written.
+    fn deserialize_raw_or<'de, D>(
+        deserializer: D,
+    ) -> result::Result<RawOr<Self>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(RawOrVisitor::new("a string"))
+    }
+}
+
+/// Provide a default implementation of InterpolatableValue that works for
+/// any type which supports `FromStr<Err = Error>` and `Display`.
+///
+/// Conceptually, this is equivalent to the following, which doesn't work
+/// even on nightly Rust with `#[feature(specialization)]` enabled, for
+/// some reason that would probably take a long GitHub issues discussion to
+/// sort out.
+///
+/// ```text
+/// impl<T, E> InterpolatableValue for T
+///     where T: FromStr<Err = E> + Display + Clone + Eq,
+///           E: IntoInvalidValueError
+/// {
+///     default fn iv_from_str(s: &str) -> Result<Self> {
+///         FromStr::from_str(s).map_err(|err| {
+///             err.into_invalid_value_error("???", s)
+///         })
+///     }
+///
+///     default fn fmt_iv(&self, f: &mut fmt::Formatter<'_>) -> std::fmt::Result {
+///         self.fmt(f)
+///     }
+/// }
+/// ```
+macro_rules! impl_interpolatable_value {
+    ($ty:ty) => {
+        impl $crate::v2::interpolation::InterpolatableValue for $ty {
+            fn iv_from_str(s: &str) -> Result<Self> {
+                use $crate::v2::interpolation::IntoInvalidValueError;
+                fn convert_err<E>(err: E, input: &str) -> Error
+                where
+                    E: IntoInvalidValueError,
+                {
+                    err.into_invalid_value_error(stringify!($ty), input)
+                }
+
+                FromStr::from_str(s).map_err(|err| convert_err(err, s))
+            }
+
+            fn fmt_iv(&self, f: &mut fmt::Formatter<'_>) -> ::std::fmt::Result {
+                use std::fmt::Display;
+                self.fmt(f)
+            }
+        }
+    };
+}
+
+impl_interpolatable_value!(String);
+
+impl IntoInvalidValueError for ParseIntError {}
+
+/// Implement `InterpolatableValue` for a numeric type.  Unlike other
+/// values, numbers are serialized as numbers, not strings.
+macro_rules! impl_interpolatable_number {
+    ($ty:ty) => {
+        impl InterpolatableValue for $ty {
+            fn iv_from_str(s: &str) -> Result<Self> {
+                s.parse().map_err(|err: ParseIntError| {
+                    err.into_invalid_value_error(stringify!($ty), s)
+                })
+            }
+
+            fn fmt_iv(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.fmt(f)
+            }
+
+            fn serialize_iv<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                self.serialize(serializer)
+            }
+
+            fn deserialize_raw_or<'de, D>(
+                deserializer: D,
+            ) -> result::Result<RawOr<Self>, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                // Floating point numbers are rejected, instead of being
+                // rounded or truncated.
+                deserializer
+                    .deserialize_any(RawOrVisitor::new("an integer or a string"))
+            }
+        }
+    };
+}
+
+impl_interpolatable_number!(i16);
+impl_interpolatable_number!(u16);
+impl_interpolatable_number!(u32);
+impl_interpolatable_number!(u64);
+
+/// This can be parsed and formatted, but not using the usual APIs.
+impl InterpolatableValue for PathBuf {
+    fn iv_from_str(s: &str) -> Result<Self> {
+        Ok(Path::new(s).to_owned())
+    }
+
+    fn fmt_iv(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display().fmt(f)
+    }
+}
+
+/// A wrapper type to make `format!` call `fmt_iv` instead of `fmt`.
+struct DisplayInterpolatableValue<'a, V>(&'a V)
+where
+    V: InterpolatableValue;
+
+impl<'a, T> Display for DisplayInterpolatableValue<'a, T>
+where
+    T: InterpolatableValue,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            DisplayInterpolatableValue(val) => val.fmt_iv(f),
+        }
+    }
+}
+
+/// Either a raw, unparsed string, or a value of the specified type.  This
+/// is the internal, private implementation of `RawOr`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RawOrValue<T>
+where
+    T: InterpolatableValue,
+{
+    /// A raw value.  Invariant: This is valid, but it contains actual
+    /// references to environment variables.  If we can parse a string,
+    /// we always do, and we store it as `Value`.
+    Raw(String),
+    /// A parsed value.
+    Value(T),
+}
+
+/// Either an unparsed interpolation string, or a fully-parsed value.  We
+/// use this representation because:
+///
+/// 1. Almost any string value in `docker-compose.yml` may contain an
+///    environment variable interpolation of the form `"$VAR"` or
+///    `"${VAR}"`, and we normally want to preserve these values in their
+///    uninterpolated form when manipulating `docker-compose.yml` files.
+/// 2. When we do actually need to manipate a complex string field of a
+///    `docker-compose.yml` file, we prefer to do it using the parsed
+///    representation.
+///
+/// Hence `RawOr<T>`, which can represent both unparsed and parsed values,
+/// and switch between them in a controlled fashion.
+///
+/// We normally create `RawOr<T>` values using one of `value`, `escape` or
+/// `raw`, as shown below.
+///
+/// ```
+/// use std::string::ToString;
+/// use compose_yml::v2 as dc;
+///
+/// // We can call `escape`, `value` and `raw` with explicit type
+/// // parameters using the following syntax.
+/// assert_eq!("bridge",
+///            dc::escape::<dc::NetworkMode, _>("bridge").unwrap().to_string());
+///
+/// // But typically, when working with `RawOr`, we'll be passing values
+/// // into a context where the type is known, allowing type interference
+/// // to supply type parameters to the `value`, `escape` and `raw` functions
+/// // automatically.  So let's simulate that using a helper function.
+/// fn nm_string(nm: dc::RawOr<dc::NetworkMode>) -> String {
+///   nm.to_string()
+/// }
+///
+/// // This is how we'll normally create `RawOr` values.
+/// assert_eq!("bridge", nm_string(dc::value(dc::NetworkMode::Bridge)));
+/// assert_eq!("bridge", nm_string(dc::escape("bridge").unwrap()));
+/// assert_eq!("container:$$FOO", nm_string(dc::escape("container:$FOO").unwrap()));
+/// assert_eq!("$NETWORK_MODE", nm_string(dc::raw("$NETWORK_MODE").unwrap()));
+///
+/// // If we call `escape`, we have to pass it a string which parses to
+/// // correct type, or it will return an error.  Similar rules apply to `raw`
+/// // if no actual interpolations are present in the string.  This is part of
+/// // our "verify as much as possible" philosophy.
+/// assert!(dc::escape::<dc::NetworkMode, _>("invalid").is_err());
+/// assert!(dc::raw::<dc::NetworkMode, _>("invalid").is_err());
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct RawOr<T>(RawOrValue<T>, Redaction)
+where
+    T: InterpolatableValue;
+
+/// `InterpolatableValue` is basically just a string that we parse for
+/// internal use, so we can merge it as though it were a simple string,
+/// without getting into the internal details of whatever it might contain.
+/// So go ahead and use the default implementation of `MergeOverride` as if
+/// we were a primitive type.
+impl<T: InterpolatableValue> MergeOverride for RawOr<T> {}
+
+/// Convert a raw string containing variable interpolations into a
+/// `RawOr<T>` value.  See `RawOr<T>` for examples of how to use this API.
+pub fn raw<T, S>(s: S) -> Result<RawOr<T>>
+where
+    T: InterpolatableValue,
+    S: Into<String>,
+{
+    let raw: String = s.into();
+    validate(&raw)?;
+    match unescape_str(&raw) {
+        // We can unescape it, so either parse it or fail.
+        Ok(unescaped) => {
+            let parsed: T = InterpolatableValue::iv_from_str(&unescaped)?;
+            Ok(RawOr(RawOrValue::Value(parsed), Redaction::default()))
+        }
+        // It's valid but we can't unescape it, which means that it contains
+        // environment references that we want to leave as raw strings.
+        Err(_) => Ok(RawOr(RawOrValue::Raw(raw), Redaction::default())),
+    }
+}
+
+/// Escape a string and convert it into a `RawOr<T>` value.  See `RawOr<T>`
+/// for examples of how to use this API.
+pub fn escape<T, S>(s: S) -> Result<RawOr<T>>
+where
+    T: InterpolatableValue,
+    S: AsRef<str>,
+{
+    let value: T = InterpolatableValue::iv_from_str(s.as_ref())?;
+    Ok(RawOr(RawOrValue::Value(value), Redaction::default()))
+}
+
+/// Convert a value into a `RawOr<T>` value, taking ownership of the
+/// original value.  See `RawOr<T>` for examples of how to use this API.
+pub fn value<T>(v: T) -> RawOr<T>
+where
+    T: InterpolatableValue,
+{
+    RawOr(RawOrValue::Value(v), Redaction::default())
+}
+
+impl<T> RawOr<T>
+where
+    T: InterpolatableValue,
+{
+    /// Either return a `&T` for this `RawOr<T>`, or return an error if
+    /// parsing the value would require performing interpolation.
+    ///
+    /// ```
+    /// use compose_yml::v2 as dc;
+    ///
+    /// let bridge = dc::value(dc::NetworkMode::Bridge);
+    /// assert_eq!(bridge.value().unwrap(), &dc::NetworkMode::Bridge);
+    /// ```
+    pub fn value(&self) -> Result<&T> {
+        match *self {
+            RawOr(RawOrValue::Value(ref val), _) => Ok(val),
+            // Because of invariants on RawOrValue, we know `unescape_str`
+            // should always return an error.
+            RawOr(RawOrValue::Raw(ref raw), _) => Err(unescape_str(raw).unwrap_err()),
+        }
+    }
+
+    /// Either return a mutable `&mut T` for this `RawOr<T>`, or return an
+    /// error if parsing the value would require performing interpolation.
+    ///
+    /// ```
+    /// use compose_yml::v2 as dc;
+    ///
+    /// let mut mode = dc::value(dc::NetworkMode::Bridge);
+    /// *mode.value_mut().unwrap() = dc::NetworkMode::Host;
+    /// assert_eq!(mode.value_mut().unwrap(), &dc::NetworkMode::Host);
+    /// ```
+    pub fn value_mut(&mut self) -> Result<&mut T> {
+        match *self {
+            RawOr(RawOrValue::Value(ref mut val), ref mut redaction) => {
+                // We can't keep our redacted form up to date if the value
+                // changes.
+                *redaction = Redaction::default();
+                Ok(val)
+            }
+            // Because of invariants on RawOrValue, we know `unescape_str`
+            // should always return an error.
+            RawOr(RawOrValue::Raw(ref raw), _) => Err(unescape_str(raw).unwrap_err()),
+        }
+    }
+
+    /// Return a `&mut T` for this `RawOr<T>`, performing any necessary
+    /// environment variable interpolations using the supplied `env` object
+    /// and updating the value in place.
+    pub fn interpolate_env(&mut self, env: &dyn Environment) -> Result<&mut T> {
+        let RawOr(ref mut inner, _) = *self;
+
+        // We have to very careful about how we destructure this value to
+        // avoid winding up with two `mut` references to `self`, and
+        // thereby making the borrow checker sad.  This means our code
+        // looks very weird.  There may be a way to simplify it.
+        //
+        // This is one of those fairly rare circumstances where we actually
+        // work around the borrow checker in a non-obvious way.
+        if let RawOrValue::Value(ref mut val) = *inner {
+            // We already have a parsed value, so just return that.
+            Ok(val)
+        } else {
+            let new_val = if let RawOrValue::Raw(ref raw) = *inner {
+                let interpolated = interpolate_env(raw, env)?;
+                InterpolatableValue::iv_from_str(&interpolated)?
+            } else {
+                unreachable!()
+            };
+            *inner = RawOrValue::Value(new_val);
+            if let RawOrValue::Value(ref mut val) = *inner {
+                Ok(val)
+            } else {
+                unreachable!()
+            }
+        }
+    }
+
+    /// Return a `&mut T` for this `RawOr<T>`, performing any necessary
+    /// environment variable interpolations using the system environment
+    /// and updating the value in place.
+    ///
+    /// ```
+    /// use std::env;
+    /// use std::str::FromStr;
+    /// use compose_yml::v2 as dc;
+    ///
+    /// env::set_var("NETWORK_MODE", "host");
+    /// let mut mode: dc::RawOr<dc::NetworkMode> =
+    ///   FromStr::from_str("$NETWORK_MODE").unwrap();
+    ///
+    /// // Before interpolation.
+    /// assert_eq!("$NETWORK_MODE", mode.to_string());
+    ///
+    /// // Interpolate.
+    /// assert_eq!(mode.interpolate().unwrap(), &dc::NetworkMode::Host);
+    ///
+    /// // After interpolation.
+    /// assert_eq!("host", mode.to_string());
+    /// ```
+    pub fn interpolate(&mut self) -> Result<&mut T> {
+        let env = OsEnvironment::new();
+        self.interpolate_env(&env)
+    }
+
+    /// Return the redacted form of this value, if it was interpolated
+    /// using any variables marked as sensitive in
+    /// `InterpolateOptions::sensitive`.  This is what `Display` and `Debug`
+    /// show, and what `File::write_redacted` writes.
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use compose_yml::v2 as dc;
+    /// use compose_yml::v2::InterpolateAll;
+    ///
+    /// let mut env = BTreeMap::new();
+    /// env.insert("DB_PASSWORD".to_owned(), "hunter2".to_owned());
+    /// let mut opts = dc::InterpolateOptions::new();
+    /// opts.sensitive.add_pattern("PASSWORD").unwrap();
+    ///
+    /// let mut url: dc::RawOr<String> =
+    ///     dc::raw("postgres://app:${DB_PASSWORD}@db/app").unwrap();
+    /// url.interpolate_all_env_with(&env, &opts).unwrap();
+    /// assert_eq!(url.value().unwrap(), "postgres://app:hunter2@db/app");
+    /// assert_eq!(url.redacted(), Some("postgres://app:********@db/app"));
+    /// assert_eq!(url.to_string(), "postgres://app:********@db/app");
+    /// ```
+    pub fn redacted(&self) -> Option<&str> {
+        self.1 .0.as_deref()
+    }
+}
+
+impl<T> Display for RawOr<T>
+where
+    T: InterpolatableValue,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            RawOr(_, Redaction(Some(ref redacted))) => write!(f, "{}", redacted),
+            RawOr(RawOrValue::Raw(ref raw), _) => write!(f, "{}", raw),
+            RawOr(RawOrValue::Value(ref value), _) => {
+                let s = format!("{}", DisplayInterpolatableValue(value));
+                write!(f, "{}", escape_str(&s))
+            }
+        }
+    }
+}
+
+impl<T> Serialize for RawOr<T>
+where
+    T: InterpolatableValue,
+{
+    fn serialize<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            RawOr(_, Redaction(Some(ref redacted))) if is_redacting() => {
+                serializer.serialize_str(redacted)
+            }
+            RawOr(RawOrValue::Raw(ref raw), _) => serializer.serialize_str(raw),
+            RawOr(RawOrValue::Value(ref value), _) => value.serialize_iv(serializer),
+        }
+    }
+}
+
+/// We show the redacted form of values interpolated from sensitive
+/// variables, so that they don't leak into logs.
+impl<T> fmt::Debug for RawOr<T>
+where
+    T: InterpolatableValue + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            RawOr(_, Redaction(Some(ref redacted))) => {
+                f.debug_tuple("Redacted").field(redacted).finish()
+            }
+            RawOr(ref inner, _) => f.debug_tuple("RawOr").field(inner).finish(),
+        }
+    }
+}
+
+impl<T> FromStr for RawOr<T>
+where
+    T: InterpolatableValue,
+{
+    type Err = Error;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        raw(s)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for RawOr<T>
+where
+    T: InterpolatableValue,
+{
+    fn deserialize<D>(deserializer: D) -> result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_raw_or(deserializer)
+    }
+}
+
+/// Deserializes a `RawOr` from a string, or from an integer which we
+/// treat the same as the equivalent string.
+struct RawOrVisitor<T> {
+    /// What we expect to find, for error messages.
+    expecting: &'static str,
+    /// The type we're deserializing.
+    marker: PhantomData<T>,
+}
+
+impl<T> RawOrVisitor<T> {
+    /// Create a new visitor.
+    fn new(expecting: &'static str) -> RawOrVisitor<T> {
+        RawOrVisitor {
+            expecting,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, T> de::Visitor<'de> for RawOrVisitor<T>
+where
+    T: InterpolatableValue,
+{
+    type Value = RawOr<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}", self.expecting)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> result::Result<RawOr<T>, E> {
+        RawOr::from_str(value).map_err(|err| E::custom(format!("{}", err)))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> result::Result<RawOr<T>, E> {
+        self.visit_str(&value.to_string())
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> result::Result<RawOr<T>, E> {
+        self.visit_str(&value.to_string())
+    }
+}
+
+/// A function called by `InterpolateAll::visit_raw` with the path to each
+/// uninterpolated `RawOr` value and its interpolation string.
+pub type RawVisitor<'a> = dyn FnMut(&[String], &str) + 'a;
+
+/// A function called by `InterpolateAll::visit_raw_mut` with the path to
+/// each uninterpolated `RawOr` value and its interpolation string.  It may
+/// return a `RawRewrite` to replace the existing value.
+pub type RawVisitorMut<'a> =
+    dyn FnMut(&[String], &str) -> Result<Option<RawRewrite>> + 'a;
+
+/// A replacement for an uninterpolated `RawOr` value, returned by a
+/// `RawVisitorMut`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawRewrite {
+    /// The new interpolation string.  If this contains no references to
+    /// variables, it will be parsed.
+    pub template: String,
+
+    /// The redacted form of the new value, as it should be displayed.  See
+    /// `RawOr::redacted`.
+    pub redacted: Option<String>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    pub _hidden: (),
+}
+
+impl RawRewrite {
+    /// Replace a value with a new interpolation string.
+    pub fn new<S: Into<String>>(template: S) -> RawRewrite {
+        RawRewrite {
+            template: template.into(),
+            redacted: None,
+            _hidden: (),
+        }
+    }
+}
+
+/// Support for environment variable interpolation.
+///
+/// As with `docker-compose`, we only interpolate values, not map keys, so
+/// keys in fields like `environment`, `labels` and `networks` are always
+/// used literally.  Numeric fields like `cpu_shares` are represented as
+/// `RawOr` values, so they may contain interpolations, too.
+pub trait InterpolateAll {
+    /// Recursively walk over this type, interpolating all `RawOr` values
+    /// containing references to the system environment.
+    fn interpolate_all(&mut self) -> Result<()> {
+        self.interpolate_all_with(&InterpolateOptions::default())
+    }
+
+    /// Like `interpolate_all`, but using `options` to control how we
+    /// interpolate.
+    fn interpolate_all_with(
+        &mut self,
+        options: &InterpolateOptions<'_>,
+    ) -> Result<()> {
+        self.interpolate_all_env_with(&OsEnvironment::new(), options)
+    }
+
+    /// Like `interpolate_all`, but looking up variables in `env` instead
+    /// of the system environment.
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use compose_yml::v2 as dc;
+    /// use compose_yml::v2::InterpolateAll;
+    ///
+    /// let template: dc::RawOr<dc::Image> = dc::raw("example:$TAG").unwrap();
+    /// for tag in &["1.0", "2.0"] {
+    ///     let mut env = BTreeMap::new();
+    ///     env.insert("TAG".to_owned(), tag.to_string());
+    ///     let mut image = template.clone();
+    ///     image.interpolate_all_env(&env).unwrap();
+    ///     assert_eq!(image.to_string(), format!("example:{}", tag));
+    /// }
+    /// ```
+    fn interpolate_all_env(&mut self, env: &dyn Environment) -> Result<()> {
+        self.interpolate_all_env_with(env, &InterpolateOptions::default())
+    }
+
+    /// Like `interpolate_all`, but looking up variables in `env` and using
+    /// `options` to control how we interpolate.
+    fn interpolate_all_env_with(
+        &mut self,
+        env: &dyn Environment,
+        options: &InterpolateOptions<'_>,
+    ) -> Result<()> {
+        self.interpolate_all_audited(env, options)?;
+        Ok(())
+    }
+
+    /// Like `interpolate_all_env_with`, but return a record of each field
+    /// we interpolated, including the original interpolation string and
+    /// where each variable's value came from.
+    ///
+    /// ```
+    /// use std::io;
+    /// use compose_yml::v2 as dc;
+    /// use compose_yml::v2::InterpolateAll;
+    ///
+    /// let env = dc::DotEnvEnvironment::read(io::Cursor::new("TAG=1.0\n")).unwrap();
+    /// let mut image: dc::RawOr<dc::Image> =
+    ///     dc::raw("${REGISTRY:-example}/web:$TAG").unwrap();
+    /// let records = image
+    ///     .interpolate_all_audited(&env, &dc::InterpolateOptions::new())
+    ///     .unwrap();
+    /// assert_eq!(image.to_string(), "example/web:1.0");
+    /// assert_eq!(records[0].template, "${REGISTRY:-example}/web:$TAG");
+    /// assert_eq!(records[0].variables[0].name, "REGISTRY");
+    /// assert_eq!(records[0].variables[0].source, dc::VariableSource::Default);
+    /// assert_eq!(records[0].variables[1].name, "TAG");
+    /// assert_eq!(records[0].variables[1].source, dc::VariableSource::DotEnv(None));
+    /// ```
+    fn interpolate_all_audited(
+        &mut self,
+        env: &dyn Environment,
+        options: &InterpolateOptions<'_>,
+    ) -> Result<Vec<InterpolationRecord>> {
+        let mut records = vec![];
+        self.visit_raw_mut(&mut vec![], &mut |path, template| {
+            let interpolated =
+                interpolate_helper(template, Mode::Interpolate, env, options)?;
+            // If we left any variables unexpanded, we can't parse the
+            // result, so leave the whole value alone.
+            if interpolated.unexpanded {
+                Ok(None)
+            } else {
+                records.push(InterpolationRecord::new(
+                    path,
+                    template,
+                    interpolated.variables,
+                ));
+                let mut rewrite = RawRewrite::new(escape_str(&interpolated.text));
+                if interpolated.used_sensitive {
+                    rewrite.redacted = Some(escape_str(&interpolated.redacted));
+                }
+                Ok(Some(rewrite))
+            }
+        })?;
+        Ok(records)
+    }
+
+    /// Interpolate the variables which are defined in `env`, and leave
+    /// references to any other variables in place for a later stage.
+    /// Values which no longer contain any references are parsed.
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use compose_yml::v2 as dc;
+    /// use compose_yml::v2::InterpolateAll;
+    ///
+    /// let mut env = BTreeMap::new();
+    /// env.insert("IMAGE_TAG".to_owned(), "1.0".to_owned());
+    ///
+    /// let mut value: dc::RawOr<String> =
+    ///     dc::raw("$$HOME/${ENV:-dev}/$IMAGE_TAG").unwrap();
+    /// value.interpolate_partial(&env).unwrap();
+    /// assert_eq!(value.to_string(), "$$HOME/${ENV:-dev}/1.0");
+    /// ```
+    fn interpolate_partial(&mut self, env: &dyn Environment) -> Result<()> {
+        let options = InterpolateOptions::default();
+        self.visit_raw_mut(&mut vec![], &mut |_path, template| {
+            let interpolated =
+                interpolate_helper(template, Mode::Partial, env, &options)?;
+            let mut rewrite = RawRewrite::new(interpolated.text);
+            if interpolated.used_sensitive {
+                rewrite.redacted = Some(interpolated.redacted);
+            }
+            Ok(Some(rewrite))
+        })
+    }
+
+    /// List every environment variable referenced by an uninterpolated
+    /// `RawOr` value, along with where it's used.
+    ///
+    /// ```
+    /// use std::str::FromStr;
+    /// use compose_yml::v2 as dc;
+    /// use compose_yml::v2::InterpolateAll;
+    ///
+    /// let yaml = r#"---
+    /// version: "2"
+    /// services:
+    ///   web:
+    ///     image: "example/web:${TAG:-latest}"
+    ///     environment:
+    ///       API_KEY: "${API_KEY?set API_KEY}"
+    /// "#;
+    /// let file = dc::File::from_str(yaml).unwrap();
+    /// let refs = file.variable_references();
+    /// assert_eq!(refs.len(), 2);
+    /// assert_eq!(refs[0].name, "API_KEY");
+    /// assert_eq!(refs[0].path.to_string(), "services.web.environment.API_KEY");
+    /// assert!(refs[0].required);
+    /// assert_eq!(refs[1].name, "TAG");
+    /// assert_eq!(refs[1].path.field, "image");
+    /// assert_eq!(refs[1].default.as_deref(), Some("latest"));
+    /// ```
+    fn variable_references(&self) -> Vec<VariableReference> {
+        let mut refs = vec![];
+        self.visit_raw(&mut vec![], &mut |path, template| {
+            VariableReference::find_all(path, template, &mut refs)
+        });
+        refs
+    }
+
+    /// Call `visitor` with the path and interpolation string of every
+    /// uninterpolated `RawOr` value.  Paths are relative to `self`, and
+    /// use the same keys as the YAML file.  The default implementation
+    /// visits nothing.
+    fn visit_raw(&self, _path: &mut Vec<String>, _visitor: &mut RawVisitor<'_>) {}
+
+    /// Like `visit_raw`, but if `visitor` returns a new interpolation
+    /// string, replace the existing one.  The default implementation
+    /// visits nothing.
+    fn visit_raw_mut(
+        &mut self,
+        _path: &mut Vec<String>,
+        _visitor: &mut RawVisitorMut<'_>,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl InterpolateAll for i16 {}
+impl InterpolateAll for u16 {}
+impl InterpolateAll for u32 {}
+impl InterpolateAll for u64 {}
+impl InterpolateAll for bool {}
+impl InterpolateAll for String {}
+impl InterpolateAll for () {}
+
+impl<T: InterpolateAll> InterpolateAll for Option<T> {
+    fn visit_raw(&self, path: &mut Vec<String>, visitor: &mut RawVisitor<'_>) {
+        if let Some(ref v) = *self {
+            v.visit_raw(path, visitor);
+        }
+    }
+
+    fn visit_raw_mut(
+        &mut self,
+        path: &mut Vec<String>,
+        visitor: &mut RawVisitorMut<'_>,
+    ) -> Result<()> {
+        if let Some(ref mut v) = *self {
+            v.visit_raw_mut(path, visitor)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: InterpolateAll> InterpolateAll for Vec<T> {
+    fn visit_raw(&self, path: &mut Vec<String>, visitor: &mut RawVisitor<'_>) {
+        for (i, v) in self.iter().enumerate() {
+            path.push(i.to_string());
+            v.visit_raw(path, visitor);
+            path.pop();
+        }
+    }
+
+    fn visit_raw_mut(
+        &mut self,
+        path: &mut Vec<String>,
+        visitor: &mut RawVisitorMut<'_>,
+    ) -> Result<()> {
+        for (i, v) in self.iter_mut().enumerate() {
+            path.push(i.to_string());
+            v.visit_raw_mut(path, visitor)?;
+            path.pop();
+        }
+        Ok(())
+    }
+}
+
+impl<K, T> InterpolateAll for BTreeMap<K, T>
+where
+    K: Ord + Clone + Display,
+    T: InterpolateAll,
+{
+    fn visit_raw(&self, path: &mut Vec<String>, visitor: &mut RawVisitor<'_>) {
+        for (k, v) in self.iter() {
+            path.push(k.to_string());
+            v.visit_raw(path, visitor);
+            path.pop();
+        }
+    }
+
+    fn visit_raw_mut(
+        &mut self,
+        path: &mut Vec<String>,
+        visitor: &mut RawVisitorMut<'_>,
+    ) -> Result<()> {
+        for (k, v) in self.iter_mut() {
+            path.push(k.to_string());
+            v.visit_raw_mut(path, visitor)?;
+            path.pop();
+        }
+        Ok(())
+    }
+}
+
+impl<T: InterpolatableValue> InterpolateAll for RawOr<T> {
+    fn visit_raw(&self, path: &mut Vec<String>, visitor: &mut RawVisitor<'_>) {
+        if let RawOr(RawOrValue::Raw(ref template), _) = *self {
+            visitor(path, template);
+        }
+    }
+
+    fn visit_raw_mut(
+        &mut self,
+        path: &mut Vec<String>,
+        visitor: &mut RawVisitorMut<'_>,
+    ) -> Result<()> {
+        if let RawOr(RawOrValue::Raw(ref template), _) = *self {
+            if let Some(rewrite) = visitor(path, template)? {
+                *self = raw(rewrite.template)?;
+                self.1 = Redaction(rewrite.redacted);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Derive `InterpolateAll` for a custom struct type, by recursively
+/// interpolating all fields.
+macro_rules! derive_interpolate_all_for {
+    ($ty:ident, { $( $field:ident $( => $key:literal )? ),+ }) => {
+        /// Recursively visit all fields in the structure.
+        impl $crate::v2::interpolation::InterpolateAll for $ty {
+            fn visit_raw(
+                &self,
+                path: &mut Vec<String>,
+                visitor: &mut $crate::v2::interpolation::RawVisitor<'_>,
+            ) {
+                $(
+                    path.push(interpolate_field_key!($field $(, $key)?).to_owned());
+                    self.$field.visit_raw(path, visitor);
+                    path.pop();
+                )+
+            }
+
+            fn visit_raw_mut(
+                &mut self,
+                path: &mut Vec<String>,
+                visitor: &mut $crate::v2::interpolation::RawVisitorMut<'_>,
+            ) -> Result<()> {
+                $(
+                    path.push(interpolate_field_key!($field $(, $key)?).to_owned());
+                    self.$field.visit_raw_mut(path, visitor)?;
+                    path.pop();
+                )+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The YAML key for a field in `derive_interpolate_all_for`, which is the
+/// name of the field unless the field is renamed.
+macro_rules! interpolate_field_key {
+    ($field:ident) => {
+        stringify!($field)
+    };
+    ($field:ident, $key:literal) => {
+        $key
+    };
+}
@@ -0,0 +1,236 @@
+//! A parser for `docker-compose.yml` interpolation strings like
+//! `"${FOO:-default}/$BAR"`.
+
+use crate::errors::*;
+
+/// The operator in a braced expansion like `${FOO:-default}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Operator {
+    /// `${FOO-default}`: use a default if the variable is unset.
+    Default,
+    /// `${FOO?message}`: fail with an error if the variable is unset.
+    Required,
+    /// `${FOO+alternate}`: use an alternate value if the variable is set.
+    Alternate,
+}
+
+/// A single variable expansion, like `$FOO` or `${FOO:-default}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Expansion {
+    /// The name of the variable.
+    pub(crate) name: String,
+    /// Should a set-but-empty variable be treated as unset?  This is the
+    /// `:` in `${FOO:-default}`.
+    pub(crate) colon: bool,
+    /// The operator, if any.
+    pub(crate) operator: Option<Operator>,
+    /// The argument to the operator, which may itself contain expansions.
+    pub(crate) arg: Vec<Segment>,
+    /// The original source text of `arg`, without any unescaping.
+    pub(crate) arg_source: String,
+    /// The original source text of this expansion, including the `$`.
+    pub(crate) source: String,
+}
+
+/// Part of a parsed interpolation string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Segment {
+    /// Literal text, with any `$$` escapes already replaced by `$`.
+    Literal(String),
+    /// A variable expansion.
+    Expansion(Expansion),
+}
+
+/// Parse an interpolation string into a list of segments.
+pub(crate) fn parse(input: &str) -> Result<Vec<Segment>> {
+    let mut parser = Parser {
+        input,
+        chars: input.chars().collect(),
+        pos: 0,
+    };
+    parser.parse_segments(false)
+}
+
+/// Is `c` allowed at the start of a variable name?
+fn is_name_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+/// Is `c` allowed after the start of a variable name?
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Our parser state.
+struct Parser<'a> {
+    /// The original input, for error messages.
+    input: &'a str,
+    /// The characters of our input.
+    chars: Vec<char>,
+    /// Our current position in `chars`.
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Look at the character `offset` characters ahead of us.
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).cloned()
+    }
+
+    /// Look at the current character.
+    fn peek(&self) -> Option<char> {
+        self.peek_at(0)
+    }
+
+    /// Build a syntax error pointing at `pos`.
+    fn error(&self, pos: usize, message: &str) -> Error {
+        Error::InterpolateInvalidSyntax {
+            input: self.input.to_owned(),
+            column: pos + 1,
+            message: message.to_owned(),
+        }
+    }
+
+    /// Parse literal text and expansions until we reach the end of the
+    /// input or, if `in_braces` is true, an unmatched `}`.
+    fn parse_segments(&mut self, in_braces: bool) -> Result<Vec<Segment>> {
+        let mut segments = vec![];
+        let mut literal = String::new();
+        loop {
+            match self.peek() {
+                None => break,
+                Some('}') if in_braces => break,
+                Some('$') if self.peek_at(1) == Some('$') => {
+                    literal.push('$');
+                    self.pos += 2;
+                }
+                Some('$') => {
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(literal.split_off(0)));
+                    }
+                    segments.push(Segment::Expansion(self.parse_expansion()?));
+                }
+                Some(c) => {
+                    literal.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(segments)
+    }
+
+    /// Parse a variable name, or return `None` if there isn't one.
+    fn parse_name(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(c) if is_name_start(c) => {}
+            _ => return None,
+        }
+        let start = self.pos;
+        while self.peek().map(is_name_char).unwrap_or(false) {
+            self.pos += 1;
+        }
+        Some(self.chars[start..self.pos].iter().collect())
+    }
+
+    /// Parse an expansion starting with `$`.
+    fn parse_expansion(&mut self) -> Result<Expansion> {
+        let start = self.pos;
+        self.pos += 1;
+
+        if self.peek() != Some('{') {
+            let name = self.parse_name().ok_or_else(|| {
+                self.error(self.pos, "expected a variable name or `{`")
+            })?;
+            return Ok(Expansion {
+                name,
+                colon: false,
+                operator: None,
+                arg: vec![],
+                arg_source: String::new(),
+                source: self.chars[start..self.pos].iter().collect(),
+            });
+        }
+
+        self.pos += 1;
+        let name = self
+            .parse_name()
+            .ok_or_else(|| self.error(self.pos, "expected a variable name"))?;
+        let colon = self.peek() == Some(':');
+        if colon {
+            self.pos += 1;
+        }
+        let operator = match self.peek() {
+            Some('-') => Some(Operator::Default),
+            Some('?') => Some(Operator::Required),
+            Some('+') => Some(Operator::Alternate),
+            Some('}') if !colon => None,
+            _ if colon => {
+                return Err(self.error(self.pos, "expected `-`, `?` or `+` after `:`"))
+            }
+            _ => return Err(self.error(self.pos, "expected `}` or an operator")),
+        };
+        if operator.is_some() {
+            self.pos += 1;
+        }
+        let arg_start = self.pos;
+        let arg = if operator.is_some() {
+            self.parse_segments(true)?
+        } else {
+            vec![]
+        };
+        let arg_source = self.chars[arg_start..self.pos].iter().collect();
+        if self.peek() != Some('}') {
+            return Err(self.error(start, "unterminated `${`"));
+        }
+        self.pos += 1;
+        Ok(Expansion {
+            name,
+            colon,
+            operator,
+            arg,
+            arg_source,
+            source: self.chars[start..self.pos].iter().collect(),
+        })
+    }
+}
+
+#[test]
+fn parse_handles_nested_expansions() {
+    let segments = parse("a$$b ${FOO:-${X}-$Y}").unwrap();
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[0], Segment::Literal("a$b ".to_owned()));
+    match &segments[1] {
+        Segment::Expansion(exp) => {
+            assert_eq!(exp.name, "FOO");
+            assert!(exp.colon);
+            assert_eq!(exp.operator, Some(Operator::Default));
+            assert_eq!(exp.arg.len(), 3);
+            assert_eq!(exp.arg_source, "${X}-$Y");
+            assert_eq!(exp.source, "${FOO:-${X}-$Y}");
+        }
+        other => panic!("unexpected segment {:?}", other),
+    }
+}
+
+#[test]
+fn parse_reports_column_of_syntax_errors() {
+    let examples = vec![
+        ("$", 2),
+        ("ab $}", 5),
+        ("${}", 3),
+        ("${foo }", 6),
+        ("${foo:=bar}", 7),
+        ("x ${FOO:-${BAR}", 3),
+    ];
+    for (input, expected) in examples {
+        match parse(input) {
+            Err(Error::InterpolateInvalidSyntax { column, .. }) => {
+                assert_eq!(column, expected, "{:?}", input)
+            }
+            other => panic!("unexpected result for {:?}: {:?}", input, other),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Records of where interpolated values came from.
+
+use std::path::PathBuf;
+
+use crate::v2::FieldPath;
+
+/// Where the value of a variable came from during interpolation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VariableSource {
+    /// The variable was set in the system environment.
+    Os,
+    /// The variable was set in a `.env` file, which was loaded from the
+    /// specified path if we know it.
+    DotEnv(Option<PathBuf>),
+    /// The variable was set in some other `Environment`, such as a map.
+    Other,
+    /// The variable wasn't set, so we used the default value from the
+    /// file, as in `${VAR:-default}`.
+    Default,
+    /// The variable wasn't set and had no default value.  It was handled
+    /// according to `InterpolateOptions::undefined`, or it was used in an
+    /// expression like `${VAR:+alternate}` which doesn't need a value.
+    Undefined,
+}
+
+/// A variable used while interpolating a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariableUse {
+    /// The name of the variable.
+    pub name: String,
+
+    /// Where its value came from.
+    pub source: VariableSource,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    pub _hidden: (),
+}
+
+impl VariableUse {
+    /// Create a new `VariableUse`.
+    pub(crate) fn new(name: &str, source: VariableSource) -> VariableUse {
+        VariableUse {
+            name: name.to_owned(),
+            source,
+            _hidden: (),
+        }
+    }
+}
+
+/// A record of how one field was interpolated, as returned by
+/// `InterpolateAll::interpolate_all_audited`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterpolationRecord {
+    /// The interpolated field.
+    pub path: FieldPath,
+
+    /// The original interpolation string.
+    pub template: String,
+
+    /// The variables used to interpolate `template`, in the order we
+    /// looked them up.
+    pub variables: Vec<VariableUse>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    pub _hidden: (),
+}
+
+impl InterpolationRecord {
+    /// Create a new record for the field at `path`, which is relative to
+    /// the top of the file.
+    pub(crate) fn new(
+        path: &[String],
+        template: &str,
+        variables: Vec<VariableUse>,
+    ) -> InterpolationRecord {
+        InterpolationRecord {
+            path: FieldPath::new(path),
+            template: template.to_owned(),
+            variables,
+            _hidden: (),
+        }
+    }
+}
+
+#[test]
+fn interpolation_records_show_where_values_came_from() {
+    use super::{InterpolateAll, InterpolateOptions, LayeredEnvironment};
+    use crate::v2::{DotEnvEnvironment, File};
+    use std::collections::BTreeMap;
+    use std::io;
+    use std::str::FromStr;
+
+    let yaml = r#"---
+services:
+  web:
+    image: "example/web:${TAG}"
+    environment:
+      MODE: "${MODE:-dev}"
+      PLAIN: "plain"
+version: "2"
+"#;
+    let mut overrides = BTreeMap::new();
+    overrides.insert("TAG".to_owned(), "2.0".to_owned());
+    let dot_env = DotEnvEnvironment::read(io::Cursor::new("TAG=1.0\n")).unwrap();
+    let mut env = LayeredEnvironment::new();
+    env.push(overrides);
+    env.push(dot_env);
+
+    let mut file = File::from_str(yaml).unwrap();
+    let records = file
+        .interpolate_all_audited(&env, &InterpolateOptions::new())
+        .unwrap();
+    let summary: Vec<_> = records
+        .iter()
+        .map(|r| {
+            let vars: Vec<_> = r
+                .variables
+                .iter()
+                .map(|v| (v.name.as_str(), v.source.clone()))
+                .collect();
+            (
+                r.path.service.as_deref(),
+                r.path.field.as_str(),
+                r.template.as_str(),
+                vars,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                Some("web"),
+                "environment.MODE",
+                "${MODE:-dev}",
+                vec![("MODE", VariableSource::Default)],
+            ),
+            (
+                Some("web"),
+                "image",
+                "example/web:${TAG}",
+                vec![("TAG", VariableSource::Other)],
+            ),
+        ]
+    );
+}
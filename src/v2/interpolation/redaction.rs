@@ -0,0 +1,136 @@
+//! Hiding the values of sensitive variables when printing interpolated
+//! values.
+
+use regex::Regex;
+use std::cell::Cell;
+use std::collections::BTreeSet;
+
+use crate::errors::*;
+
+/// The text we display in place of a sensitive variable's value.
+pub(crate) const REDACTED_PLACEHOLDER: &str = "********";
+
+/// A set of variables whose values should never be displayed, such as
+/// passwords and API keys.
+///
+/// ```
+/// use compose_yml::v2 as dc;
+///
+/// let mut sensitive = dc::SensitiveVariables::new();
+/// sensitive.add_name("DB_PASSWORD");
+/// sensitive.add_pattern("(?i)token|secret").unwrap();
+/// assert!(sensitive.is_sensitive("DB_PASSWORD"));
+/// assert!(sensitive.is_sensitive("GITHUB_TOKEN"));
+/// assert!(!sensitive.is_sensitive("TAG"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SensitiveVariables {
+    /// Variables which were listed explicitly.
+    names: BTreeSet<String>,
+    /// Patterns matching the names of sensitive variables.
+    patterns: Vec<Regex>,
+}
+
+impl SensitiveVariables {
+    /// Create a new, empty `SensitiveVariables`.
+    pub fn new() -> SensitiveVariables {
+        Default::default()
+    }
+
+    /// Mark the variable `name` as sensitive.
+    pub fn add_name<S: Into<String>>(&mut self, name: S) {
+        self.names.insert(name.into());
+    }
+
+    /// Mark all variables with names matching the regular expression
+    /// `pattern` as sensitive.  Patterns are unanchored, so `PASSWORD`
+    /// matches `DB_PASSWORD`.
+    pub fn add_pattern(&mut self, pattern: &str) -> Result<()> {
+        let re = Regex::new(pattern)
+            .map_err(|_| Error::invalid_value("regular expression", pattern))?;
+        self.patterns.push(re);
+        Ok(())
+    }
+
+    /// Is the variable `name` sensitive?
+    pub fn is_sensitive(&self, name: &str) -> bool {
+        self.names.contains(name) || self.patterns.iter().any(|re| re.is_match(name))
+    }
+}
+
+/// The redacted form of a `RawOr` value, if it was interpolated using
+/// sensitive variables.  This is ignored when comparing values, because
+/// it's only used for display.
+#[derive(Clone, Default)]
+pub(crate) struct Redaction(pub(crate) Option<String>);
+
+impl PartialEq for Redaction {
+    fn eq(&self, _other: &Redaction) -> bool {
+        true
+    }
+}
+
+impl Eq for Redaction {}
+
+thread_local! {
+    /// Should `RawOr` values be serialized in their redacted form?
+    static REDACTING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Call `f`, serializing any `RawOr` values in their redacted form.
+pub(crate) fn with_redaction<R, F: FnOnce() -> R>(f: F) -> R {
+    /// Restores the previous state, even if `f` panics.
+    struct Restore(bool);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0;
+            REDACTING.with(|redacting| redacting.set(previous));
+        }
+    }
+
+    let _restore = Restore(REDACTING.with(|redacting| redacting.replace(true)));
+    f()
+}
+
+/// Are we currently inside `with_redaction`?
+pub(crate) fn is_redacting() -> bool {
+    REDACTING.with(|redacting| redacting.get())
+}
+
+#[test]
+fn redacted_files_hide_sensitive_values() {
+    use super::{InterpolateAll, InterpolateOptions};
+    use crate::v2::File;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    let yaml = r#"---
+services:
+  web:
+    image: "example/web:${TAG}"
+    environment:
+      API_KEY: "key-${API_KEY}"
+version: "2"
+"#;
+    let mut env = BTreeMap::new();
+    env.insert("TAG".to_owned(), "1.0".to_owned());
+    env.insert("API_KEY".to_owned(), "s3cr3t".to_owned());
+    let mut opts = InterpolateOptions::new();
+    opts.sensitive.add_name("API_KEY");
+
+    let mut file = File::from_str(yaml).unwrap();
+    file.interpolate_all_env_with(&env, &opts).unwrap();
+
+    let mut redacted = vec![];
+    file.write_redacted(&mut redacted).unwrap();
+    let redacted = String::from_utf8(redacted).unwrap();
+    assert!(redacted.contains("key-********"));
+    assert!(redacted.contains("example/web:1.0"));
+    assert!(!redacted.contains("s3cr3t"));
+    assert!(!format!("{:?}", file).contains("s3cr3t"));
+
+    let mut written = vec![];
+    file.write(&mut written).unwrap();
+    assert!(String::from_utf8(written).unwrap().contains("key-s3cr3t"));
+}
@@ -0,0 +1,106 @@
+//! Listing the environment variables used by interpolation strings.
+
+use super::parser::{parse, Operator, Segment};
+use crate::v2::FieldPath;
+
+/// A reference to an environment variable from an interpolation string,
+/// as returned by `InterpolateAll::variable_references`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariableReference {
+    /// The name of the variable.
+    pub name: String,
+
+    /// The field containing this reference.
+    pub path: FieldPath,
+
+    /// The default value used when the variable is unset, as written in
+    /// the file, if the reference looks like `${VAR-default}` or
+    /// `${VAR:-default}`.
+    pub default: Option<String>,
+
+    /// Is this variable required, as in `${VAR?err}` or `${VAR:?err}`?
+    /// `docker-compose` will refuse to use the file if these aren't set.
+    pub required: bool,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    pub _hidden: (),
+}
+
+impl VariableReference {
+    /// Append all the variables referenced by `template` to `refs`.
+    /// `path` is the path to the field containing `template`, relative to
+    /// the top of the file.
+    pub(crate) fn find_all(
+        path: &[String],
+        template: &str,
+        refs: &mut Vec<VariableReference>,
+    ) {
+        // Values stored in a `RawOr` have already been validated.
+        if let Ok(segments) = parse(template) {
+            find_in_segments(&FieldPath::new(path), &segments, refs);
+        }
+    }
+}
+
+/// Append all the variables referenced by `segments` to `refs`, including
+/// any which appear in default values and other operator arguments.
+fn find_in_segments(
+    path: &FieldPath,
+    segments: &[Segment],
+    refs: &mut Vec<VariableReference>,
+) {
+    for segment in segments {
+        if let Segment::Expansion(exp) = segment {
+            refs.push(VariableReference {
+                name: exp.name.clone(),
+                path: path.clone(),
+                default: match exp.operator {
+                    Some(Operator::Default) => Some(exp.arg_source.clone()),
+                    _ => None,
+                },
+                required: exp.operator == Some(Operator::Required),
+                _hidden: (),
+            });
+            find_in_segments(path, &exp.arg, refs);
+        }
+    }
+}
+
+#[test]
+fn variable_references_include_nested_defaults_and_renamed_fields() {
+    use super::InterpolateAll;
+    use crate::v2::Service;
+
+    let yaml = r#"---
+env_file:
+  - "${ENV_DIR:-${HOME}/env}/web.env"
+ports:
+  - "${PORT:?}:80"
+"#;
+    let service: Service = serde_yaml::from_str(yaml).unwrap();
+    let refs = service.variable_references();
+    let summary: Vec<_> = refs
+        .iter()
+        .map(|r| {
+            (
+                r.path.field.as_str(),
+                r.name.as_str(),
+                r.default.as_deref(),
+                r.required,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("env_file.0", "ENV_DIR", Some("${HOME}/env"), false),
+            ("env_file.0", "HOME", None, false),
+            ("ports.0", "PORT", None, true),
+        ]
+    );
+    assert!(refs.iter().all(|r| r.path.service.is_none()));
+}
@@ -0,0 +1,174 @@
+//! Checking that services only refer to things defined in the same file.
+
+use super::common::*;
+
+/// What kind of thing an `InvalidReference` refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceKind {
+    /// A service in the top-level `services` section.
+    Service,
+    /// A named volume in the top-level `volumes` section.
+    Volume,
+    /// A network in the top-level `networks` section.
+    Network,
+}
+
+impl fmt::Display for ReferenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferenceKind::Service => write!(f, "service"),
+            ReferenceKind::Volume => write!(f, "volume"),
+            ReferenceKind::Network => write!(f, "network"),
+        }
+    }
+}
+
+/// A reference to a service, volume or network which isn't defined in the
+/// file, as returned by `File::check_references`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidReference {
+    /// The field containing the reference.
+    pub path: FieldPath,
+
+    /// What kind of thing we're referring to.
+    pub kind: ReferenceKind,
+
+    /// The name we couldn't find.
+    pub name: String,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    pub _hidden: (),
+}
+
+impl fmt::Display for InvalidReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: undefined {} {:?}", self.path, self.kind, self.name)
+    }
+}
+
+/// Find all the undefined references in `file`.  See
+/// `File::check_references`.
+pub(crate) fn find_invalid_references(file: &File) -> Vec<InvalidReference> {
+    let mut found = vec![];
+    for (service_name, service) in &file.services {
+        let mut check = |field: String, kind: ReferenceKind, name: &str| {
+            let defined = match kind {
+                ReferenceKind::Service => file.services.contains_key(name),
+                ReferenceKind::Volume => file.volumes.contains_key(name),
+                // Every file has a `default` network, even if it isn't
+                // declared.
+                ReferenceKind::Network => {
+                    name == "default" || file.networks.contains_key(name)
+                }
+            };
+            if !defined {
+                found.push(InvalidReference {
+                    path: FieldPath::in_service(service_name, &field),
+                    kind,
+                    name: name.to_owned(),
+                    _hidden: (),
+                });
+            }
+        };
+
+        for name in service.depends_on.keys() {
+            check(format!("depends_on.{}", name), ReferenceKind::Service, name);
+        }
+        for (idx, link) in service.links.iter().enumerate() {
+            if let Ok(link) = link.value() {
+                check(
+                    format!("links.{}", idx),
+                    ReferenceKind::Service,
+                    link.name(),
+                );
+            }
+        }
+        for (idx, volumes_from) in service.volumes_from.iter().enumerate() {
+            if let Ok(VolumesFrom {
+                source: ServiceOrContainer::Service(name),
+                ..
+            }) = volumes_from.value()
+            {
+                check(
+                    format!("volumes_from.{}", idx),
+                    ReferenceKind::Service,
+                    name,
+                );
+            }
+        }
+        if let Some(Ok(NetworkMode::Service(name))) =
+            service.network_mode.as_ref().map(|m| m.value())
+        {
+            check("network_mode".to_owned(), ReferenceKind::Service, name);
+        }
+        // Services extended from other files are checked by
+        // `File::resolve_extends`.
+        if let Some(extends) = service.extends.as_ref().filter(|e| e.file.is_none()) {
+            if let Ok(name) = extends.service.value() {
+                check("extends.service".to_owned(), ReferenceKind::Service, name);
+            }
+        }
+        for (idx, mount) in service.volumes.iter().enumerate() {
+            if let Ok(VolumeMount {
+                host: Some(HostVolume::Name(name)),
+                ..
+            }) = mount.value()
+            {
+                check(format!("volumes.{}", idx), ReferenceKind::Volume, name);
+            }
+        }
+        for name in service.networks.keys() {
+            check(format!("networks.{}", name), ReferenceKind::Network, name);
+        }
+    }
+    found
+}
+
+#[test]
+fn undefined_references_are_reported() {
+    let yaml = r#"---
+services:
+  web:
+    image: "example/web"
+    depends_on: [db, cache]
+    links: ["db:database", "queue"]
+    volumes_from: ["db", "ghost:ro", "container:logs"]
+    network_mode: "service:proxy"
+    extends:
+      service: base
+    volumes:
+      - "data:/data"
+      - "uploads:/uploads"
+      - "./src:/src"
+    networks: [default, front, back]
+  db:
+    image: "postgres"
+volumes:
+  data: {}
+networks:
+  front: {}
+version: "2"
+"#;
+    let file = File::from_str(yaml).unwrap();
+    let found: Vec<_> = file
+        .check_references()
+        .iter()
+        .map(|r| r.to_string())
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            r#"services.web.depends_on.cache: undefined service "cache""#,
+            r#"services.web.links.1: undefined service "queue""#,
+            r#"services.web.volumes_from.1: undefined service "ghost""#,
+            r#"services.web.network_mode: undefined service "proxy""#,
+            r#"services.web.extends.service: undefined service "base""#,
+            r#"services.web.volumes.1: undefined volume "uploads""#,
+            r#"services.web.networks.back: undefined network "back""#,
+        ]
+    );
+}
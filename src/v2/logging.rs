@@ -94,7 +94,7 @@ options:
 
     let merged = logging1.merge_override(&logging2);
     assert_eq!(merged.driver.unwrap().value().unwrap(), "d2");
-    assert!(merged.options.get("opt1").is_none());
+    assert!(!merged.options.contains_key("opt1"));
     assert_eq!(
         merged
             .options
@@ -2,6 +2,8 @@ use super::common::*;
 
 /// The size of a block of memory. This can be serialized as a
 /// Docker-compatible size string using specifiers like `k`, `m` and `g`.
+/// When parsing, we also accept the uppercase specifiers used by many
+/// version 3 examples, like `50M`.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct MemorySize(usize);
 
@@ -37,6 +39,8 @@ impl MemorySize {
 impl_interpolatable_value!(MemorySize);
 
 impl fmt::Display for MemorySize {
+    // `usize::is_multiple_of` would require Rust 1.87.
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let bytes = self.to_bytes();
         if bytes == 0 {
@@ -61,17 +65,18 @@ impl FromStr for MemorySize {
 
     fn from_str(s: &str) -> Result<Self> {
         lazy_static! {
-            static ref MEM_SIZE: Regex = Regex::new("^([0-9]+)([bkmg])?$").unwrap();
+            static ref MEM_SIZE: Regex =
+                Regex::new("^([0-9]+)([bkmgBKMG])?$").unwrap();
         }
         let caps = MEM_SIZE
             .captures(s)
             .ok_or_else(|| Error::invalid_value("memory size", s))?;
         let value: usize = caps.get(1).unwrap().as_str().parse().unwrap();
         match caps.get(2).map(|c| c.as_str()) {
-            None | Some("b") => Ok(MemorySize::bytes(value)),
-            Some("k") => Ok(MemorySize::kb(value)),
-            Some("m") => Ok(MemorySize::mb(value)),
-            Some("g") => Ok(MemorySize::gb(value)),
+            None | Some("b") | Some("B") => Ok(MemorySize::bytes(value)),
+            Some("k") | Some("K") => Ok(MemorySize::kb(value)),
+            Some("m") | Some("M") => Ok(MemorySize::mb(value)),
+            Some("g") | Some("G") => Ok(MemorySize::gb(value)),
             _ => unreachable!("Unexpected error parsing MemorySize <{}>", s),
         }
     }
@@ -95,4 +100,5 @@ fn memory_size_supports_string_serialization() {
     }
 
     assert_eq!(MemorySize::bytes(10), MemorySize::from_str("10b").unwrap());
+    assert_eq!(MemorySize::mb(50), MemorySize::from_str("50M").unwrap());
 }
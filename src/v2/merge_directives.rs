@@ -0,0 +1,296 @@
+//! Support for the `!reset` and `!override` tags in override files.
+
+use serde_json::Value;
+use yaml_rust::scanner::{Scanner, Token, TokenType};
+use yaml_rust::{Yaml, YamlEmitter, YamlLoader};
+
+use super::common::*;
+use super::merge_provenance::{value_paths, ValuePath};
+
+/// What to do with a tagged value when merging an override file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Directive {
+    /// `!reset`: Remove the field or map entry from the merged file.
+    Reset,
+    /// `!override`: Replace the merged value with ours, instead of
+    /// merging it.
+    Override,
+}
+
+/// The `!reset` and `!override` tags found in an override file.
+///
+/// `MergeOverride` can only add to a file, so these tags allow an
+/// override to clear a field, delete a map entry or replace a list
+/// wholesale, the same way `docker compose` does:
+///
+/// ```yaml
+/// services:
+///   web:
+///     ports: !override
+///       - "8080:80"
+///     healthcheck: !reset null
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct MergeDirectives {
+    /// Each tagged value, with its path in the file, in document order.
+    directives: Vec<(Vec<String>, Directive)>,
+    /// Each tagged list item, with its path in the file.  We can't apply
+    /// these, because the item may have moved when the lists were merged.
+    list_items: Vec<(Vec<String>, Directive)>,
+}
+
+impl MergeDirectives {
+    /// Find the directives in the YAML document `text`.  If `text` can't
+    /// be parsed, we return no directives, and leave it to the YAML
+    /// parser to report the error.
+    pub(crate) fn parse(text: &str) -> MergeDirectives {
+        let paths = value_paths(text);
+        let mut result = MergeDirectives::default();
+        for tag in scan_tags(text) {
+            if let Some(ValuePath { path, in_sequence }) = paths.get(&tag.value_offset)
+            {
+                if *in_sequence {
+                    result.list_items.push((path.clone(), tag.directive));
+                } else {
+                    result.directives.push((path.clone(), tag.directive));
+                }
+            }
+        }
+        result
+    }
+
+    /// Parse `text` as a file, leaving out any values tagged with
+    /// `!reset`.  These values are only placeholders, and they may not
+    /// have the right type for their field.  Returns an error if a list
+    /// item is tagged with `!reset` or `!override`.  The caller is responsible for
+    /// validating the file.
+    pub(crate) fn read_file(&self, text: &str) -> Result<File> {
+        if let Some((path, directive)) = self.list_items.first() {
+            let path = path.join(".");
+            return Err(match directive {
+                Directive::Reset => Error::ResetListItem { path },
+                Directive::Override => Error::OverrideListItem { path },
+            });
+        }
+        if self.directives.is_empty() {
+            return Ok(serde_yaml::from_str(text)?);
+        }
+
+        // Parse the file without our tags, so that tagged values are typed
+        // the same way as untagged ones.  We use `yaml_rust` instead of
+        // `serde_yaml::Value`, because it keeps the original text of
+        // floating point numbers like `1.10`.
+        let docs = YamlLoader::load_from_str(&without_tags(text))
+            .map_err(Error::validation_failed)?;
+        let mut doc = docs.into_iter().next().unwrap_or(Yaml::Null);
+        for (path, directive) in self.directives.iter().rev() {
+            if *directive == Directive::Reset {
+                remove_yaml(&mut doc, path);
+            }
+        }
+        let mut untagged = String::new();
+        YamlEmitter::new(&mut untagged)
+            .dump(&doc)
+            .map_err(Error::validation_failed)?;
+        Ok(serde_yaml::from_str(&untagged)?)
+    }
+
+    /// Apply our directives to `merged`, the result of merging `ovr` into
+    /// the previous files.
+    pub(crate) fn apply(&self, merged: &mut File, ovr: &File) -> Result<()> {
+        if self.directives.is_empty() {
+            return Ok(());
+        }
+        let mut merged_value =
+            serde_json::to_value(&*merged).map_err(Error::validation_failed)?;
+        let ovr_value = serde_json::to_value(ovr).map_err(Error::validation_failed)?;
+        for (path, directive) in &self.directives {
+            match directive {
+                Directive::Reset => remove_json(&mut merged_value, path),
+                Directive::Override => {
+                    if let Some(value) = lookup_json(&ovr_value, path) {
+                        if let Some(target) = lookup_json_mut(&mut merged_value, path)
+                        {
+                            *target = value.clone();
+                        }
+                    }
+                }
+            }
+        }
+        *merged =
+            serde_json::from_value(merged_value).map_err(Error::validation_failed)?;
+        Ok(())
+    }
+}
+
+/// One of our directive tags in a YAML document.
+struct Tag {
+    /// The directive.
+    directive: Directive,
+    /// The offset of the tag itself, in characters.
+    tag_offset: usize,
+    /// The length of the tag, in characters.
+    tag_len: usize,
+    /// The offset of the tagged value, in characters.
+    value_offset: usize,
+}
+
+/// Find each of our directive tags in `text`.  Other tags are ignored.
+fn scan_tags(text: &str) -> Vec<Tag> {
+    let mut result: Vec<Tag> = vec![];
+    let mut pending = None;
+    for Token(mark, token) in Scanner::new(text.chars()) {
+        match token {
+            TokenType::Tag(handle, suffix) => {
+                let directive = match (&handle[..], &suffix[..]) {
+                    ("!", "reset") => Some(Directive::Reset),
+                    ("!", "override") => Some(Directive::Override),
+                    _ => None,
+                };
+                pending = directive.map(|directive| Tag {
+                    directive,
+                    tag_offset: mark.index(),
+                    tag_len: handle.chars().count() + suffix.chars().count(),
+                    value_offset: 0,
+                });
+            }
+            // An anchor may appear between a tag and its value.
+            TokenType::Anchor(_) => {}
+            _ => {
+                // The parser reports a tagged value at the position of the
+                // next token, even if the value is empty.
+                if let Some(mut tag) = pending.take() {
+                    tag.value_offset = mark.index();
+                    if result.iter().all(|t| t.value_offset != tag.value_offset) {
+                        result.push(tag);
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Replace our directive tags in `text` with spaces, leaving everything
+/// else where it was.
+fn without_tags(text: &str) -> String {
+    let tags = scan_tags(text);
+    text.chars()
+        .enumerate()
+        .map(|(idx, c)| {
+            let in_tag = tags
+                .iter()
+                .any(|t| t.tag_offset <= idx && idx < t.tag_offset + t.tag_len);
+            if in_tag {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Remove the value at `path` from a YAML document.
+fn remove_yaml(value: &mut Yaml, path: &[String]) {
+    let (last, parents) = match path.split_last() {
+        Some(split) => split,
+        None => {
+            *value = Yaml::Null;
+            return;
+        }
+    };
+    let parent = parents
+        .iter()
+        .try_fold(value, |current, elem| match current {
+            Yaml::Hash(map) => map.get_mut(&Yaml::String(elem.to_owned())),
+            Yaml::Array(items) => items.get_mut(elem.parse::<usize>().ok()?),
+            _ => None,
+        });
+    match parent {
+        Some(Yaml::Hash(map)) => {
+            map.remove(&Yaml::String(last.to_owned()));
+        }
+        Some(Yaml::Array(items)) => {
+            if let Ok(idx) = last.parse::<usize>() {
+                if idx < items.len() {
+                    items.remove(idx);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Remove the map entry at `path` from a JSON value.  List items in the
+/// merged file may have moved, so we don't remove them, and `read_file`
+/// rejects `!reset` on list items.
+fn remove_json(value: &mut Value, path: &[String]) {
+    if let Some((last, parents)) = path.split_last() {
+        if let Some(Value::Object(map)) = lookup_json_mut(value, parents) {
+            map.remove(last);
+        }
+    }
+}
+
+/// Look up the value at `path` in a JSON value.
+fn lookup_json<'a>(value: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |current, elem| match current {
+        Value::Array(items) => items.get(elem.parse::<usize>().ok()?),
+        _ => current.get(elem),
+    })
+}
+
+/// Look up the value at `path` in a JSON value, for modification.
+fn lookup_json_mut<'a>(
+    value: &'a mut Value,
+    path: &[String],
+) -> Option<&'a mut Value> {
+    path.iter().try_fold(value, |current, elem| match current {
+        Value::Array(items) => items.get_mut(elem.parse::<usize>().ok()?),
+        _ => current.get_mut(elem),
+    })
+}
+
+#[test]
+fn merge_directives_are_found_at_their_paths() {
+    let yaml = r#"---
+services:
+  web:
+    image: !reset
+    ports: !override
+      - "8080:80"
+    dns: !reset []
+    labels: !custom
+      a: b
+version: "2"
+"#;
+    let directives = MergeDirectives::parse(yaml);
+    let path = |p: &str| p.split('.').map(|s| s.to_owned()).collect::<Vec<_>>();
+    assert_eq!(
+        directives.directives,
+        vec![
+            (path("services.web.image"), Directive::Reset),
+            (path("services.web.ports"), Directive::Override),
+            (path("services.web.dns"), Directive::Reset),
+        ]
+    );
+}
+
+#[test]
+fn reset_values_are_removed_without_changing_other_values() {
+    let yaml = r#"---
+services:
+  web:
+    image: "example/web"
+    hostname: 1.10
+    privileged: !override true
+    ports: !reset
+version: "2"
+"#;
+    let directives = MergeDirectives::parse(yaml);
+    let file = directives.read_file(yaml).unwrap();
+    let web = &file.services["web"];
+    assert_eq!(web.hostname, Some(value("1.10".to_owned())));
+    assert_eq!(web.privileged, Some(true));
+    assert!(web.ports.is_empty());
+}
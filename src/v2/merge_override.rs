@@ -1,5 +1,7 @@
 //! Merging two `docker-compose.yml` files together.
 
+use serde::de::DeserializeOwned;
+use serde_json::Value;
 use std::collections::btree_map;
 use std::collections::BTreeMap;
 use std::marker::Sized;
@@ -30,6 +32,7 @@ macro_rules! assert_merge {
 impl MergeOverride for i16 {}
 impl MergeOverride for u16 {}
 impl MergeOverride for u32 {}
+impl MergeOverride for u64 {}
 impl MergeOverride for bool {}
 impl MergeOverride for String {}
 impl MergeOverride for () {}
@@ -48,10 +51,10 @@ impl<T: MergeOverride> MergeOverride for Option<T> {
     /// neither is present, return `None`.
     fn merge_override(&self, ovr: &Self) -> Self {
         match (self, ovr) {
-            (&Some(ref x), &Some(ref y)) => Some(x.merge_override(y)),
-            (&Some(ref x), &None) => Some(x.to_owned()),
-            (&None, &Some(ref y)) => Some(y.to_owned()),
-            (&None, &None) => None,
+            (Some(x), Some(y)) => Some(x.merge_override(y)),
+            (Some(x), None) => Some(x.to_owned()),
+            (None, Some(y)) => Some(y.to_owned()),
+            (None, None) => None,
         }
     }
 }
@@ -121,17 +124,180 @@ fn btree_map_merges_by_key() {
     assert_merge!(BTreeMap<&'static str, Vec<bool>>, map1, map2, expected);
 }
 
-/// Derive `MergeOverride` for a custom struct type, by recursively merging
-/// all fields.
+/// Merge two lists, appending any items from `ovr` which don't already
+/// appear in `base`.  This is how `docker-compose` merges fields like
+/// `ports`, `expose` and `dns`.
+pub(crate) fn merge_unique<T>(base: &[T], ovr: &[T]) -> Vec<T>
+where
+    T: Clone + PartialEq,
+{
+    merge_by_key(base, ovr, |_| None::<()>)
+}
+
+#[test]
+fn merge_unique_skips_duplicates() {
+    assert_eq!(merge_unique(&[1, 2], &[2, 3, 3]), vec![1, 2, 3]);
+}
+
+/// Use `ovr` in place of `base`, unless `ovr` is empty because the
+/// override file didn't set it.  This is how `docker-compose` merges list
+/// fields like `group_add`, which it treats as single values.
+pub(crate) fn merge_replace<T>(base: &[T], ovr: &[T]) -> Vec<T>
+where
+    T: Clone,
+{
+    if ovr.is_empty() {
+        base.to_vec()
+    } else {
+        ovr.to_vec()
+    }
+}
+
+#[test]
+fn merge_replace_keeps_base_only_if_override_is_empty() {
+    assert_eq!(merge_replace(&[1, 2], &[2, 3]), vec![2, 3]);
+    assert_eq!(merge_replace(&[1, 2], &[]), vec![1, 2]);
+}
+
+/// Merge two lists of items identified by `key`.  An item in `ovr`
+/// replaces any item in `base` with the same key, and the remaining items
+/// in `ovr` are appended unless they're already present.  Items for which
+/// `key` returns `None` only match identical items.  This is how
+/// `docker-compose` merges fields like `volumes`, which are identified by
+/// their container path.
+pub(crate) fn merge_by_key<T, K, F>(base: &[T], ovr: &[T], key: F) -> Vec<T>
+where
+    T: Clone + PartialEq,
+    K: PartialEq,
+    F: Fn(&T) -> Option<K>,
+{
+    let mut result = base.to_vec();
+    for item in ovr {
+        let item_key = key(item);
+        let existing = result
+            .iter()
+            .position(|r| r == item || (item_key.is_some() && key(r) == item_key));
+        match existing {
+            Some(idx) => result[idx] = item.clone(),
+            None => result.push(item.clone()),
+        }
+    }
+    result
+}
+
+#[test]
+fn merge_by_key_replaces_matching_items() {
+    let key = |s: &&str| s.split('=').next().map(|k| k.to_owned());
+    assert_eq!(
+        merge_by_key(&["a=1", "b=1"], &["b=2", "c=2"], key),
+        vec!["a=1", "b=2", "c=2"]
+    );
+}
+
+/// How `File::merge_three_way` matches up the items of a list field.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ListItems {
+    /// The list is a single value, like `command`, and it is only merged
+    /// as a whole.
+    Atomic,
+    /// Items are identified by their value, like `ports`.
+    Unique,
+    /// Items are identified by the key returned by this function, like
+    /// the container path of a volume.  Items without a key are
+    /// identified by their value.
+    Keyed(fn(&Value) -> Option<String>),
+}
+
+/// The rules for matching up list items in each field of a struct.  This
+/// is generated by `derive_merge_override_for`, so it always agrees with
+/// `MergeOverride`.
+pub(crate) trait ListMergeRules {
+    /// How to match up the items of `field`, which is given by its YAML
+    /// name.
+    fn list_items(field: &str) -> ListItems;
+}
+
+/// Deserialize a list item which has been converted to JSON, and look up
+/// its key using `key_fn`.
+pub(crate) fn json_item_key<T, F>(item: &Value, key_fn: F) -> Option<String>
+where
+    T: DeserializeOwned,
+    F: Fn(&T) -> Option<String>,
+{
+    let item: T = serde_json::from_value(item.to_owned()).ok()?;
+    key_fn(&item)
+}
+
+/// Derive `MergeOverride` and `ListMergeRules` for a custom struct type,
+/// by recursively merging all fields.  List fields which shouldn't just be
+/// appended may be written as `field with unique`, which uses
+/// `merge_unique`, `field with key(key_fn)`, which uses `merge_by_key`, or
+/// `field with replace`, which uses `merge_replace`.
 macro_rules! derive_merge_override_for {
-    ($ty:ident, { $( $field:ident ),+ }) => {
+    ($ty:ident, {
+        $(
+            $field:ident $( => $key:literal )?
+                $( with $rule:ident $( ( $key_fn:path ) )? )?
+        ),+
+    }) => {
         /// Recursive merge all fields in the structure.
         impl MergeOverride for $ty {
             fn merge_override(&self, ovr: &Self) -> Self {
                 $ty {
-                    $( $field: self.$field.merge_override(&ovr.$field) ),+
+                    $(
+                        $field: merge_field!(
+                            self.$field, ovr.$field $(, $rule $(, $key_fn)?)?
+                        )
+                    ),+
                 }
             }
         }
+
+        impl $crate::v2::merge_override::ListMergeRules for $ty {
+            fn list_items(field: &str) -> $crate::v2::merge_override::ListItems {
+                $(
+                    if field == interpolate_field_key!($field $(, $key)?) {
+                        return list_items!($( $rule $(, $key_fn)? )?);
+                    }
+                )+
+                $crate::v2::merge_override::ListItems::Atomic
+            }
+        }
     }
 }
+
+/// Merge a field in `derive_merge_override_for`, using `MergeOverride`
+/// unless the field has its own merge rule.
+macro_rules! merge_field {
+    ($base:expr, $ovr:expr) => {
+        $base.merge_override(&$ovr)
+    };
+    ($base:expr, $ovr:expr, unique) => {
+        $crate::v2::merge_override::merge_unique(&$base, &$ovr)
+    };
+    ($base:expr, $ovr:expr, replace) => {
+        $crate::v2::merge_override::merge_replace(&$base, &$ovr)
+    };
+    ($base:expr, $ovr:expr, key, $key_fn:path) => {
+        $crate::v2::merge_override::merge_by_key(&$base, &$ovr, $key_fn)
+    };
+}
+
+/// The `ListItems` for a field in `derive_merge_override_for`.  Fields
+/// without their own merge rule are treated as single values.
+macro_rules! list_items {
+    () => {
+        $crate::v2::merge_override::ListItems::Atomic
+    };
+    (unique) => {
+        $crate::v2::merge_override::ListItems::Unique
+    };
+    (replace) => {
+        $crate::v2::merge_override::ListItems::Atomic
+    };
+    (key, $key_fn:path) => {
+        $crate::v2::merge_override::ListItems::Keyed(|item| {
+            $crate::v2::merge_override::json_item_key(item, $key_fn)
+        })
+    };
+}
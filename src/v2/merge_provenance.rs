@@ -0,0 +1,409 @@
+//! Records of which file set each field when several files were merged.
+
+use serde_json::Value;
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::Marker;
+
+use super::common::*;
+
+/// Where the value of a merged field came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSource {
+    /// The file which last set this field, as passed to
+    /// `File::read_all_with_provenance`.
+    pub file: PathBuf,
+
+    /// The 1-based line number of the field in `file`.  If the field was
+    /// written in a different form (for example, `environment` written as
+    /// a list), this is the line of the closest enclosing field we could
+    /// find.  This is `None` if we couldn't find a line at all.
+    pub line: Option<usize>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    pub _hidden: (),
+}
+
+impl fmt::Display for FieldSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// The source of every field in a merged file, as returned by
+/// `File::read_all_with_provenance`.
+///
+/// Fields are named by their path in the merged file, separated by `.`,
+/// like `services.web.image` or `services.web.ports.0`.  Only leaf values
+/// are recorded, so maps and lists don't have a source of their own.
+/// Displaying a `MergeProvenance` shows a "blame"-style listing, with one
+/// line per field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeProvenance {
+    /// The source of each field.
+    fields: BTreeMap<String, FieldSource>,
+}
+
+impl MergeProvenance {
+    /// The source of the field `field`, if it's present in the merged
+    /// file.
+    pub fn source(&self, field: &str) -> Option<&FieldSource> {
+        self.fields.get(field)
+    }
+
+    /// Iterate over all fields and their sources, sorted by field.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &FieldSource)> {
+        self.fields
+            .iter()
+            .map(|(field, source)| (&field[..], source))
+    }
+
+    /// Record the effect of merging the file at `path`, containing `text`.
+    /// `before` is the merged file before this step (or `None` for the
+    /// first file), `ovr` is the file we merged, and `after` is the
+    /// result.
+    pub(crate) fn record(
+        &mut self,
+        path: &Path,
+        text: &str,
+        before: Option<&File>,
+        ovr: &File,
+        after: &File,
+    ) -> Result<()> {
+        let to_value =
+            |file: &File| serde_json::to_value(file).map_err(Error::validation_failed);
+        let before = before.map(to_value).transpose()?;
+        let ovr = to_value(ovr)?;
+        let after = to_value(after)?;
+        let mut recorder = Recorder {
+            file: path,
+            lines: line_numbers(text),
+            old_fields: &self.fields,
+            fields: BTreeMap::new(),
+        };
+        recorder.walk(before.as_ref(), Some(&ovr), &after, &mut vec![], Some(&[]));
+        self.fields = recorder.fields;
+        Ok(())
+    }
+}
+
+impl fmt::Display for MergeProvenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (field, source) in &self.fields {
+            writeln!(f, "{}: {}", source, field)?;
+        }
+        Ok(())
+    }
+}
+
+/// Walks over a merged file, working out the source of each field.
+struct Recorder<'a> {
+    /// The file we just merged.
+    file: &'a Path,
+    /// The line numbers of the fields in `file`.
+    lines: BTreeMap<String, usize>,
+    /// The sources we recorded for the previous merge step.
+    old_fields: &'a BTreeMap<String, FieldSource>,
+    /// The sources of the fields in the new merged file.
+    fields: BTreeMap<String, FieldSource>,
+}
+
+impl<'a> Recorder<'a> {
+    /// Record sources for every leaf in `after`.  `path` is the path to
+    /// `after`, and `src_path` is the path to `ovr` in our file, or `None`
+    /// if our file doesn't contain this value.
+    fn walk(
+        &mut self,
+        before: Option<&Value>,
+        ovr: Option<&Value>,
+        after: &Value,
+        path: &mut Vec<String>,
+        src_path: Option<&[String]>,
+    ) {
+        match after {
+            Value::Object(map) if !map.is_empty() => {
+                for (key, value) in map {
+                    let ovr = ovr.and_then(|o| o.get(key));
+                    let child_src = src_path
+                        .filter(|_| ovr.is_some())
+                        .map(|p| with_element(p, key));
+                    path.push(key.to_owned());
+                    self.walk(
+                        before.and_then(|b| b.get(key)),
+                        ovr,
+                        value,
+                        path,
+                        child_src.as_deref(),
+                    );
+                    path.pop();
+                }
+            }
+            Value::Array(items) if !items.is_empty() => {
+                for (idx, value) in items.iter().enumerate() {
+                    // List items may have moved during the merge, so look
+                    // for the same item in our file.
+                    let ovr_idx = ovr
+                        .and_then(|o| o.as_array())
+                        .and_then(|o| o.iter().position(|item| item == value));
+                    let child_src = match (src_path, ovr_idx) {
+                        (Some(p), Some(i)) => Some(with_element(p, &i.to_string())),
+                        _ => None,
+                    };
+                    path.push(idx.to_string());
+                    self.walk(
+                        before.and_then(|b| b.get(idx)),
+                        ovr_idx.and_then(|i| ovr.and_then(|o| o.get(i))),
+                        value,
+                        path,
+                        child_src.as_deref(),
+                    );
+                    path.pop();
+                }
+            }
+            _ => {
+                let field = path.join(".");
+                let changed = before != Some(after);
+                if changed || ovr == Some(after) {
+                    let line = src_path.and_then(|p| self.line(p));
+                    self.fields.insert(
+                        field,
+                        FieldSource {
+                            file: self.file.to_owned(),
+                            line,
+                            _hidden: (),
+                        },
+                    );
+                } else if let Some(old) = self.old_fields.get(&field) {
+                    self.fields.insert(field, old.clone());
+                }
+            }
+        }
+    }
+
+    /// Find the line of `path` in our file, or of its closest ancestor.
+    fn line(&self, path: &[String]) -> Option<usize> {
+        (1..=path.len())
+            .rev()
+            .find_map(|len| self.lines.get(&path[..len].join(".")).cloned())
+    }
+}
+
+/// Append `elem` to `path`.
+fn with_element(path: &[String], elem: &str) -> Vec<String> {
+    let mut result = path.to_owned();
+    result.push(elem.to_owned());
+    result
+}
+
+/// Find the line number of every field in a YAML document, indexed by its
+/// path.  Returns an empty map if `text` can't be parsed.
+fn line_numbers(text: &str) -> BTreeMap<String, usize> {
+    scan_fields(text).map(|r| r.lines).unwrap_or_default()
+}
+
+/// The location of a value in a YAML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ValuePath {
+    /// The path to the value.
+    pub(crate) path: Vec<String>,
+    /// Is the value an item in a sequence?
+    pub(crate) in_sequence: bool,
+}
+
+/// Find the path of every value in a YAML document, indexed by the
+/// character offset where the value starts.  Returns an empty map if
+/// `text` can't be parsed.
+pub(crate) fn value_paths(text: &str) -> BTreeMap<usize, ValuePath> {
+    scan_fields(text).map(|r| r.paths).unwrap_or_default()
+}
+
+/// Run a `LineReceiver` over `text`, returning `None` if it can't be
+/// parsed.
+fn scan_fields(text: &str) -> Option<LineReceiver> {
+    let mut receiver = LineReceiver {
+        stack: vec![],
+        lines: BTreeMap::new(),
+        paths: BTreeMap::new(),
+    };
+    let mut parser = Parser::new(text.chars());
+    parser.load(&mut receiver, false).ok()?;
+    Some(receiver)
+}
+
+/// A YAML collection we're currently inside.
+enum Frame {
+    /// A mapping, and the key we're expecting a value for, if any.
+    Mapping(Option<String>),
+    /// A sequence, and the index of the next item.
+    Sequence(usize),
+}
+
+/// Receives YAML parser events and records the line of each field.
+struct LineReceiver {
+    /// The collections we're inside, with the path to each.
+    stack: Vec<(Vec<String>, Frame)>,
+    /// The line of each field we've seen.
+    lines: BTreeMap<String, usize>,
+    /// The path of each value we've seen, indexed by its offset.
+    paths: BTreeMap<usize, ValuePath>,
+}
+
+impl LineReceiver {
+    /// The path of the next value in the current collection, or `None` if
+    /// the next value is a mapping key.
+    fn value_path(&self) -> Option<Vec<String>> {
+        match self.stack.last() {
+            None => Some(vec![]),
+            Some((_, Frame::Mapping(None))) => None,
+            Some((path, Frame::Mapping(Some(key)))) => Some(with_element(path, key)),
+            Some((path, Frame::Sequence(idx))) => {
+                Some(with_element(path, &idx.to_string()))
+            }
+        }
+    }
+
+    /// Record the path of the value starting at `offset`.
+    fn record_path(&mut self, offset: usize, path: Vec<String>) {
+        let in_sequence = matches!(self.stack.last(), Some((_, Frame::Sequence(_))));
+        self.paths
+            .entry(offset)
+            .or_insert(ValuePath { path, in_sequence });
+    }
+
+    /// Move past the value we just finished in the current collection.
+    fn finish_value(&mut self) {
+        match self.stack.last_mut() {
+            Some((_, Frame::Mapping(key))) => *key = None,
+            Some((_, Frame::Sequence(idx))) => *idx += 1,
+            None => {}
+        }
+    }
+}
+
+impl MarkedEventReceiver for LineReceiver {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        match ev {
+            Event::Scalar(value, ..) => match self.value_path() {
+                None => {
+                    if let Some((path, Frame::Mapping(key))) = self.stack.last_mut() {
+                        self.lines
+                            .entry(with_element(path, &value).join("."))
+                            .or_insert_with(|| mark.line());
+                        *key = Some(value);
+                    }
+                }
+                Some(path) => {
+                    self.lines
+                        .entry(path.join("."))
+                        .or_insert_with(|| mark.line());
+                    self.record_path(mark.index(), path);
+                    self.finish_value();
+                }
+            },
+            Event::Alias(_) => self.finish_value(),
+            Event::MappingStart(_) | Event::SequenceStart(_) => {
+                // We don't support complex keys, so use an empty path.
+                let value_path = self.value_path();
+                if let Some(path) = &value_path {
+                    self.record_path(mark.index(), path.clone());
+                }
+                let path = value_path.unwrap_or_default();
+                self.lines
+                    .entry(path.join("."))
+                    .or_insert_with(|| mark.line());
+                let frame = match ev {
+                    Event::MappingStart(_) => Frame::Mapping(None),
+                    _ => Frame::Sequence(0),
+                };
+                self.stack.push((path, frame));
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+                self.finish_value();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn line_numbers_are_found_for_nested_fields() {
+    let yaml = r#"---
+services:
+  web:
+    image: "example/web"
+    ports:
+      - "80:80"
+      - "443:443"
+version: "2"
+"#;
+    let lines = line_numbers(yaml);
+    assert_eq!(lines["services.web"], 3);
+    assert_eq!(lines["services.web.image"], 4);
+    assert_eq!(lines["services.web.ports.1"], 7);
+    assert_eq!(lines["version"], 8);
+}
+
+#[test]
+fn merge_provenance_records_the_last_file_to_set_each_field() {
+    let dir = TestDir::new("provenance");
+    let base = dir.join("docker-compose.yml");
+    let ovr = dir.join("docker-compose.override.yml");
+    fs::write(
+        &base,
+        r#"---
+services:
+  web:
+    image: "example/web:1.0"
+    environment:
+      MODE: prod
+    ports:
+      - "80:80"
+version: "2"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        &ovr,
+        r#"---
+services:
+  web:
+    image: "example/web:dev"
+    environment:
+      - DEBUG=1
+    ports:
+      - "9229:9229"
+version: "2"
+"#,
+    )
+    .unwrap();
+
+    let (_, provenance) =
+        File::read_all_with_provenance(&[base.clone(), ovr.clone()]).unwrap();
+    let source = |field: &str| {
+        let source = provenance.source(field).unwrap();
+        (source.file.clone(), source.line)
+    };
+    assert_eq!(source("services.web.image"), (ovr.clone(), Some(4)));
+    assert_eq!(
+        source("services.web.environment.MODE"),
+        (base.clone(), Some(6))
+    );
+    assert_eq!(
+        source("services.web.environment.DEBUG"),
+        (ovr.clone(), Some(5))
+    );
+    assert_eq!(source("services.web.ports.0"), (base.clone(), Some(8)));
+    assert_eq!(source("services.web.ports.1"), (ovr.clone(), Some(8)));
+    assert_eq!(source("version"), (ovr.clone(), Some(9)));
+    assert!(provenance
+        .to_string()
+        .contains(&format!("{}:4: services.web.image\n", ovr.display())));
+}
@@ -0,0 +1,84 @@
+//! Figure out which 2.x version of the file format is needed to support
+//! the fields we use.
+//!
+//! Versions are represented by their minor version number, so `0` is
+//! version 2 and `3` is version 2.3.
+
+use super::common::*;
+
+/// The newest 2.x minor version we support.
+pub(crate) const LATEST_MINOR_VERSION: u8 = 4;
+
+/// Convert a minor version number into a `version:` string.
+pub(crate) fn version_string(minor: u8) -> String {
+    match minor {
+        0 => "2".to_owned(),
+        _ => format!("2.{}", minor),
+    }
+}
+
+/// Convert a `version:` string into a minor version number.
+pub(crate) fn minor_version(version: &str) -> Result<u8> {
+    match version {
+        "2" => Ok(0),
+        _ => version
+            .strip_prefix("2.")
+            .and_then(|minor| minor.parse().ok())
+            .filter(|&minor| minor <= LATEST_MINOR_VERSION)
+            .ok_or_else(|| Error::UnsupportedVersion(version.to_owned())),
+    }
+}
+
+/// The earliest minor version of the file format whose schema accepts
+/// every field used by `file`.  If no version accepts all of them, we
+/// return the latest version, so that validation reports the problem.
+pub(crate) fn minimum_minor_version(file: &File) -> Result<u8> {
+    let mut value = serde_json::to_value(file).map_err(Error::validation_failed)?;
+    for minor in 0..LATEST_MINOR_VERSION {
+        let version = version_string(minor);
+        let schema = schema_for_version(&version)?;
+        value["version"] = serde_json::Value::String(version);
+        if find_unsupported_fields(schema, &value).is_empty() {
+            return Ok(minor);
+        }
+    }
+    Ok(LATEST_MINOR_VERSION)
+}
+
+#[test]
+fn minimum_version_matches_schemas() {
+    let examples = vec![
+        ("services: {web: {image: hello}}", 0),
+        (
+            "services: {web: {image: hello}}\nvolumes: {data: {labels: {a: b}}}",
+            1,
+        ),
+        (
+            "services: {web: {image: hello, healthcheck: {interval: 30s}}}",
+            1,
+        ),
+        (
+            "services: {db: {image: db}, web: {image: hello, depends_on: {db: {condition: service_healthy}}}}",
+            1,
+        ),
+        ("services: {web: {build: {context: ., target: prod}}}", 3),
+        (
+            "services: {web: {image: hello, healthcheck: {start_period: 5s}}}",
+            3,
+        ),
+    ];
+    for (yaml, minor) in examples {
+        let yaml = format!("version: \"2.4\"\n{}", yaml);
+        let mut file = File::from_str(&yaml).unwrap();
+        file.normalize_version().unwrap();
+        assert_eq!(file.version, version_string(minor), "{}", yaml);
+        validate_file(&file).unwrap();
+        if minor > 0 {
+            let older = version_string(minor - 1);
+            let (_, report) = file
+                .convert_to_version(&older, ConversionMode::Check)
+                .unwrap();
+            assert!(!report.is_empty(), "{}", yaml);
+        }
+    }
+}
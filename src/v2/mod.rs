@@ -6,20 +6,32 @@ pub use crate::errors::*;
 
 mod env_file;
 mod git_url;
-mod helpers;
+pub(crate) mod helpers;
 #[macro_use]
-mod interpolation;
-mod string_or_struct;
-mod true_or_struct;
+pub(crate) mod interpolation;
+mod merge_directives;
+pub(crate) mod string_or_struct;
+pub(crate) mod true_or_struct;
 #[macro_use]
-mod merge_override;
+pub(crate) mod merge_override;
+mod merge_provenance;
+mod minimum_version;
 mod mode_enum;
+mod rebase;
+#[cfg(test)]
+mod test_dir;
 #[macro_use]
-mod validate;
+pub(crate) mod validate;
 
 pub use git_url::GitUrl;
-pub use interpolation::{escape, raw, value, Environment, RawOr};
+pub use interpolation::{
+    escape, raw, value, DotEnvEnvironment, Environment, InterpolateAll,
+    InterpolateOptions, InterpolationRecord, LayeredEnvironment, OsEnvironment, RawOr,
+    RawRewrite, RawVisitor, RawVisitorMut, SensitiveVariables, UndefinedVariable,
+    VariableReference, VariableSource, VariableUse,
+};
 pub use merge_override::MergeOverride;
+pub use merge_provenance::{FieldSource, MergeProvenance};
 pub use mode_enum::{IpcMode, NetworkMode, PidMode, RestartMode};
 
 #[cfg(test)]
@@ -33,10 +45,21 @@ macro_rules! assert_roundtrip {
     }};
 }
 
+/// Derive `InterpolateAll`, `MergeOverride` and `ListMergeRules` for a
+/// struct.  Fields which are renamed in YAML should be written as `field =>
+/// "yaml_key"`, and lists with their own merge rules as `field with
+/// unique`, `field with key(key_fn)` or `field with replace`.
 macro_rules! derive_standard_impls_for {
-    ($ty:ident, { $( $field:ident ),+ }) => {
-        derive_interpolate_all_for!($ty, { $( $field ),+ });
-        derive_merge_override_for!($ty, { $( $field ),+ });
+    ($ty:ident, {
+        $(
+            $field:ident $( => $key:literal )?
+                $( with $rule:ident $( ( $key_fn:path ) )? )?
+        ),+
+    }) => {
+        derive_interpolate_all_for!($ty, { $( $field $( => $key )? ),+ });
+        derive_merge_override_for!($ty, {
+            $( $field $( => $key )? $( with $rule $( ( $key_fn ) )? )? ),+
+        });
     }
 }
 
@@ -46,10 +69,15 @@ macro_rules! derive_standard_impls_for {
 // Support types.
 mod aliased_name;
 mod command_line;
+mod field_path;
 mod host_mapping;
 mod image;
+mod incompatibility;
+mod invalid_reference;
 mod memory_size;
+mod override_diff;
 mod permissions;
+mod three_way_merge;
 mod volume_modes;
 
 // Basic file structure.
@@ -61,7 +89,11 @@ mod volume;
 // Service-related types.
 mod build;
 mod context;
+mod dependency;
+mod dependency_cycle;
+mod diff;
 mod extends;
+mod healthcheck;
 mod logging;
 mod network_interface;
 mod port_mapping;
@@ -77,25 +109,33 @@ pub use aliased_name::*;
 pub use build::*;
 pub use command_line::*;
 pub use context::*;
+pub use dependency::*;
+pub use diff::{Change, ChangeKind};
 pub use extends::*;
 pub use external_network::*;
+pub use field_path::FieldPath;
 pub use file::*;
+pub use healthcheck::*;
 pub use host_mapping::*;
 pub use image::*;
+pub use incompatibility::*;
+pub use invalid_reference::{InvalidReference, ReferenceKind};
 pub use logging::*;
 pub use memory_size::*;
 pub use network::*;
 pub use network_interface::*;
+pub use override_diff::InexpressibleChange;
 pub use permissions::*;
 pub use port_mapping::*;
 pub use service::*;
+pub use three_way_merge::MergeConflict;
 pub use ulimit::*;
 pub use volume::*;
 pub use volume_modes::*;
 pub use volume_mount::*;
 pub use volumes_from::*;
 
-pub(self) mod common {
+mod common {
     pub(crate) use lazy_static::lazy_static;
     #[cfg(windows)]
     pub(crate) use regex::Captures;
@@ -117,20 +157,37 @@ pub(self) mod common {
     pub(crate) use std::str::FromStr;
     pub(crate) use void::Void;
 
+    pub(crate) use super::dependency_cycle::find_dependency_cycle;
+    pub(crate) use super::diff::diff;
     pub(crate) use super::env_file::EnvFile;
     pub(crate) use super::helpers::{
         deserialize_item_or_list, deserialize_map_or_default_list,
         deserialize_map_or_key_value_list, deserialize_map_struct_or_null, is_false,
+        serialize_map_or_default_list,
+    };
+    pub(crate) use super::interpolation::with_redaction;
+    pub(crate) use super::invalid_reference::find_invalid_references;
+    pub(crate) use super::merge_directives::MergeDirectives;
+    pub(crate) use super::merge_override::{ListItems, ListMergeRules};
+    pub(crate) use super::minimum_version::{
+        minimum_minor_version, minor_version, version_string, LATEST_MINOR_VERSION,
     };
-    pub(crate) use super::interpolation::InterpolateAll;
+    pub(crate) use super::override_diff::diff_as_override;
+    pub(crate) use super::rebase::{canonical_dir, relative_dir, RebasePaths};
     pub(crate) use super::string_or_struct::{
         deserialize_opt_string_or_struct, serialize_opt_string_or_struct,
         SerializeStringOrStruct,
     };
+    #[cfg(test)]
+    pub(crate) use super::test_dir::TestDir;
+    pub(crate) use super::three_way_merge::merge_three_way;
     pub(crate) use super::true_or_struct::{
         deserialize_opt_true_or_struct, serialize_opt_true_or_struct,
     };
-    pub(crate) use super::validate::validate_file;
+    pub(crate) use super::validate::{
+        find_unsupported_fields, schema_for_version, validate_file, PathElement,
+        UnsupportedField,
+    };
 
     pub(crate) use super::*;
 }
@@ -26,7 +26,7 @@ use super::common::*;
 ///
 /// Here's a simplified example of what it looks like:
 ///
-/// ```
+/// ```text
 /// mode_enum! {
 ///     /// How should we configure the container's networking?
 ///     #[derive(Debug, Clone, PartialEq, Eq)]
@@ -167,9 +167,8 @@ mode_enum! {
         /// Use the host's PID namespace.
         ("host") => Host
     ;
-        // Use another service's namespace.  This _should_ exist, but it's
-        // not documented.  Feel free to uncomment and try.
-        //("service") => Service(String),
+        /// Use the PID namespace associated with the named service.
+        ("service") => Service(String),
         /// Use the named container's PID namespace.
         ("container") => Container(String)
     }
@@ -182,9 +181,8 @@ mode_enum! {
         /// Use the host's IPC namespace.
         ("host") => Host
     ;
-        // Use another service's namespace.  This _should_ exist, but it's
-        // not documented.  Feel free to uncomment and try.
-        //("service") => Service(String),
+        /// Use the IPC namespace associated with the named service.
+        ("service") => Service(String),
         /// Use the named container's IPC namespace.
         ("container") => Container(String)
     }
@@ -0,0 +1,286 @@
+//! Generating an override file from the differences between two files.
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use super::common::*;
+
+/// A change which `File::diff_as_override` couldn't express as an
+/// override, such as removing a field or removing an item from a list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InexpressibleChange {
+    /// The field we couldn't change.
+    pub path: FieldPath,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    pub _hidden: (),
+}
+
+impl InexpressibleChange {
+    /// Describe the field at `path`, which is relative to the top of the
+    /// file.
+    fn new(path: &[String]) -> Self {
+        InexpressibleChange {
+            path: FieldPath::new(path),
+            _hidden: (),
+        }
+    }
+}
+
+impl fmt::Display for InexpressibleChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path)
+    }
+}
+
+/// Find the smallest override which turns `base` into `target`.  See
+/// `File::diff_as_override`.
+pub(crate) fn diff_as_override(
+    base: &File,
+    target: &File,
+) -> Result<(File, Vec<InexpressibleChange>)> {
+    let mut report = vec![];
+    let ovr = File {
+        version: target.version.clone(),
+        services: diff_section(
+            "services",
+            &base.services,
+            &target.services,
+            &mut report,
+        )?,
+        volumes: diff_section("volumes", &base.volumes, &target.volumes, &mut report)?,
+        networks: diff_section(
+            "networks",
+            &base.networks,
+            &target.networks,
+            &mut report,
+        )?,
+        _hidden: (),
+    };
+    Ok((ovr, report))
+}
+
+/// Find the override entries needed to turn the `base` entries of a
+/// top-level section into the `target` entries.
+fn diff_section<T>(
+    section: &str,
+    base: &BTreeMap<String, T>,
+    target: &BTreeMap<String, T>,
+    report: &mut Vec<InexpressibleChange>,
+) -> Result<BTreeMap<String, T>>
+where
+    T: MergeOverride + PartialEq + Serialize + DeserializeOwned,
+{
+    let mut result = BTreeMap::new();
+    for name in base.keys().filter(|name| !target.contains_key(*name)) {
+        report.push(InexpressibleChange::new(&[
+            section.to_owned(),
+            name.to_owned(),
+        ]));
+    }
+    for (name, target_entry) in target {
+        let path = [section.to_owned(), name.to_owned()];
+        match base.get(name) {
+            None => {
+                result.insert(name.to_owned(), target_entry.clone());
+            }
+            Some(base_entry) if base_entry == target_entry => {}
+            Some(base_entry) => {
+                let entry = diff_entry(&path, base_entry, target_entry, report)?;
+                result.insert(name.to_owned(), entry);
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Find the smallest value which, when merged into `base`, produces
+/// `target`.  We work one field at a time, trying each of the candidates
+/// returned by `candidates` until we find one which works.
+fn diff_entry<T>(
+    path: &[String],
+    base: &T,
+    target: &T,
+    report: &mut Vec<InexpressibleChange>,
+) -> Result<T>
+where
+    T: MergeOverride + Serialize + DeserializeOwned,
+{
+    let base_value = to_object(base)?;
+    let target_value = to_object(target)?;
+    let mut keys: Vec<&String> =
+        base_value.keys().chain(target_value.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    let mut ovr = Map::new();
+    for key in keys {
+        let wanted = target_value.get(key);
+        let mut found = false;
+        let candidates = candidates(base_value.get(key), wanted);
+        for candidate in &candidates {
+            let mut trial = ovr.clone();
+            if let Some(candidate) = candidate {
+                trial.insert(key.to_owned(), candidate.clone());
+            }
+            let trial: T = match serde_json::from_value(Value::Object(trial)) {
+                Ok(trial) => trial,
+                Err(_) => continue,
+            };
+            let merged = to_object(&base.merge_override(&trial))?;
+            if merged.get(key) == wanted {
+                if let Some(candidate) = candidate {
+                    ovr.insert(key.to_owned(), candidate.clone());
+                }
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            let mut field_path = path.to_owned();
+            field_path.push(key.to_owned());
+            report.push(InexpressibleChange::new(&field_path));
+            // Include whatever we can add, even if we can't remove
+            // anything.
+            if let Some(Some(delta)) = candidates.get(1) {
+                ovr.insert(key.to_owned(), delta.clone());
+            }
+        }
+    }
+    serde_json::from_value(Value::Object(ovr)).map_err(Error::validation_failed)
+}
+
+/// The values we might use to override a field, from smallest to largest.
+/// `None` means leaving the field out of the override.
+fn candidates(base: Option<&Value>, target: Option<&Value>) -> Vec<Option<Value>> {
+    match (base, target) {
+        (_, None) => vec![None],
+        (None, Some(target)) => vec![None, Some(target.to_owned())],
+        (Some(base), Some(target)) => {
+            vec![None, Some(delta(base, target)), Some(target.to_owned())]
+        }
+    }
+}
+
+/// The parts of `target` which differ from `base`.  For maps, this is the
+/// entries which have changed, and for lists, this is the items which
+/// aren't in `base`.
+fn delta(base: &Value, target: &Value) -> Value {
+    match (base, target) {
+        (Value::Object(base), Value::Object(target)) => Value::Object(
+            target
+                .iter()
+                .filter(|(k, v)| base.get(*k) != Some(*v))
+                .map(|(k, v)| {
+                    let d = base.get(k).map_or_else(|| v.clone(), |b| delta(b, v));
+                    (k.to_owned(), d)
+                })
+                .collect(),
+        ),
+        (Value::Array(base), Value::Array(target)) => Value::Array(
+            target
+                .iter()
+                .filter(|v| !base.contains(v))
+                .cloned()
+                .collect(),
+        ),
+        _ => target.to_owned(),
+    }
+}
+
+/// Serialize `value` as a JSON object.
+fn to_object<T: Serialize>(value: &T) -> Result<Map<String, Value>> {
+    match serde_json::to_value(value).map_err(Error::validation_failed)? {
+        Value::Object(map) => Ok(map),
+        _ => Ok(Map::new()),
+    }
+}
+
+#[test]
+fn diff_as_override_finds_minimal_changes() {
+    let base = File::from_str(
+        r#"---
+services:
+  web:
+    image: "example/web:1.0"
+    privileged: true
+    environment:
+      MODE: prod
+      LOG: info
+    ports:
+      - "80:80"
+    volumes:
+      - "./data:/data"
+  worker:
+    image: "example/worker"
+version: "2"
+"#,
+    )
+    .unwrap();
+    let target = File::from_str(
+        r#"---
+services:
+  web:
+    image: "example/web:dev"
+    privileged: true
+    environment:
+      MODE: dev
+      LOG: info
+    ports:
+      - "80:80"
+      - "9229:9229"
+    volumes:
+      - "./src:/data"
+  db:
+    image: "postgres"
+version: "2"
+"#,
+    )
+    .unwrap();
+
+    let (ovr, report) = File::diff_as_override(&base, &target).unwrap();
+    let expected = File::from_str(
+        r#"---
+services:
+  web:
+    image: "example/web:dev"
+    environment:
+      MODE: dev
+    ports:
+      - "9229:9229"
+    volumes:
+      - "./src:/data"
+  db:
+    image: "postgres"
+version: "2"
+"#,
+    )
+    .unwrap();
+    assert_eq!(ovr, expected);
+    assert_eq!(ovr.services["web"].privileged, None);
+    let fields: Vec<_> = report.iter().map(|c| c.to_string()).collect();
+    assert_eq!(fields, vec!["services.worker"]);
+
+    let mut merged = base.merge_override(&ovr);
+    merged.services.remove("worker");
+    assert_eq!(merged, target);
+}
+
+#[test]
+fn diff_as_override_reports_removals() {
+    let base = File::from_str(
+        "---\nservices:\n  web:\n    image: hello\n    dns: [8.8.8.8]\nversion: \"2\"\n",
+    )
+    .unwrap();
+    let target =
+        File::from_str("---\nservices:\n  web:\n    dns: [8.8.4.4]\nversion: \"2\"\n")
+            .unwrap();
+    let (ovr, report) = File::diff_as_override(&base, &target).unwrap();
+    let fields: Vec<_> = report.iter().map(|c| c.to_string()).collect();
+    assert_eq!(fields, vec!["services.web.dns", "services.web.image"]);
+    assert_eq!(ovr.services["web"].dns.len(), 1);
+}
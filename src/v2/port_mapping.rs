@@ -55,19 +55,16 @@ impl FromStr for Ports {
 }
 
 /// An IP protocol
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
 pub enum Protocol {
     /// Transmission Control Protocol, the default.
+    #[default]
     Tcp,
     /// User Datagram Protocol.
     Udp,
 }
 
-impl Default for Protocol {
-    fn default() -> Self {
-        Protocol::Tcp
-    }
-}
+impl_interpolatable_value!(Protocol);
 
 impl fmt::Display for Protocol {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
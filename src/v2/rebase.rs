@@ -0,0 +1,198 @@
+//! Adjust the relative paths in a file, so that a file read from one
+//! directory can be merged with a file read from another.
+//!
+//! Only local paths are adjusted.  Values which still contain
+//! interpolations are left alone, because we can't tell whether they'll
+//! expand to relative paths.
+
+use std::path::Component;
+
+use super::common::*;
+use super::interpolation::InterpolatableValue;
+
+/// Types which contain paths relative to the directory of their file.
+pub(crate) trait RebasePaths {
+    /// Prefix every relative path in this value with `prefix`.
+    fn rebase_paths(&mut self, prefix: &Path);
+}
+
+impl<T: RebasePaths> RebasePaths for Option<T> {
+    fn rebase_paths(&mut self, prefix: &Path) {
+        if let Some(v) = self {
+            v.rebase_paths(prefix);
+        }
+    }
+}
+
+impl<T: RebasePaths> RebasePaths for Vec<T> {
+    fn rebase_paths(&mut self, prefix: &Path) {
+        for v in self {
+            v.rebase_paths(prefix);
+        }
+    }
+}
+
+impl<K, T: RebasePaths> RebasePaths for BTreeMap<K, T> {
+    fn rebase_paths(&mut self, prefix: &Path) {
+        for v in self.values_mut() {
+            v.rebase_paths(prefix);
+        }
+    }
+}
+
+impl<T> RebasePaths for RawOr<T>
+where
+    T: InterpolatableValue + RebasePaths,
+{
+    fn rebase_paths(&mut self, prefix: &Path) {
+        if let Ok(v) = self.value_mut() {
+            v.rebase_paths(prefix);
+        }
+    }
+}
+
+impl RebasePaths for PathBuf {
+    fn rebase_paths(&mut self, prefix: &Path) {
+        *self = rebase_path(prefix, self);
+    }
+}
+
+impl RebasePaths for File {
+    fn rebase_paths(&mut self, prefix: &Path) {
+        self.services.rebase_paths(prefix);
+    }
+}
+
+impl RebasePaths for Service {
+    fn rebase_paths(&mut self, prefix: &Path) {
+        self.build.rebase_paths(prefix);
+        self.env_files.rebase_paths(prefix);
+        self.extends.rebase_paths(prefix);
+        self.volumes.rebase_paths(prefix);
+    }
+}
+
+impl RebasePaths for Build {
+    fn rebase_paths(&mut self, prefix: &Path) {
+        // `dockerfile` is relative to `context`, so it doesn't change.
+        self.context.rebase_paths(prefix);
+    }
+}
+
+impl RebasePaths for Context {
+    fn rebase_paths(&mut self, prefix: &Path) {
+        if let Context::Dir(dir) = self {
+            dir.rebase_paths(prefix);
+        }
+    }
+}
+
+impl RebasePaths for Extends {
+    fn rebase_paths(&mut self, prefix: &Path) {
+        self.file.rebase_paths(prefix);
+    }
+}
+
+impl RebasePaths for VolumeMount {
+    fn rebase_paths(&mut self, prefix: &Path) {
+        if let Some(HostVolume::Path(path)) = &mut self.host {
+            path.rebase_paths(prefix);
+        }
+    }
+}
+
+/// Prefix `path` with `prefix` if it's relative, removing any `.`
+/// components and any `..` components which can be cancelled out.
+pub(crate) fn rebase_path(prefix: &Path, path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_owned();
+    }
+    let mut result: Vec<Component<'_>> = vec![];
+    for component in prefix.components().chain(path.components()) {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match result.last() {
+                Some(Component::Normal(_)) => {
+                    result.pop();
+                }
+                _ => result.push(component),
+            },
+            _ => result.push(component),
+        }
+    }
+    if result.is_empty() {
+        Path::new(".").to_owned()
+    } else {
+        result.iter().collect()
+    }
+}
+
+/// The absolute, canonical path of the directory containing `path`.
+pub(crate) fn canonical_dir(path: &Path) -> Result<PathBuf> {
+    let canonical = path
+        .canonicalize()
+        .map_err(|err| Error::read_file(path.to_owned(), err))?;
+    Ok(canonical
+        .parent()
+        .map(|dir| dir.to_owned())
+        .unwrap_or(canonical))
+}
+
+/// Find a relative path from the directory `base` to the directory `dir`.
+/// Both directories should be absolute and canonical.  If there's no
+/// relative path (for example, because they're on different Windows
+/// drives), we return `dir` unchanged.
+pub(crate) fn relative_dir(base: &Path, dir: &Path) -> PathBuf {
+    let base: Vec<_> = base.components().collect();
+    let dir: Vec<_> = dir.components().collect();
+    let common = base
+        .iter()
+        .zip(dir.iter())
+        .take_while(|(b, d)| b == d)
+        .count();
+    if common == 0 {
+        return dir.iter().collect();
+    }
+    let mut result = PathBuf::new();
+    for _ in common..base.len() {
+        result.push("..");
+    }
+    for component in &dir[common..] {
+        result.push(component);
+    }
+    result
+}
+
+#[test]
+fn paths_are_rebased_lexically() {
+    let examples = vec![
+        ("", "./data", "data"),
+        ("overrides", "./data", "overrides/data"),
+        ("overrides", "../data", "data"),
+        ("overrides", "..", "."),
+        ("../shared", "../data", "../data"),
+        ("overrides", "/var/data", "/var/data"),
+    ];
+    for (prefix, path, expected) in examples {
+        assert_eq!(
+            rebase_path(Path::new(prefix), Path::new(path)),
+            Path::new(expected),
+            "{:?} {:?}",
+            prefix,
+            path
+        );
+    }
+
+    assert_eq!(
+        relative_dir(Path::new("/app"), Path::new("/app/overrides")),
+        Path::new("overrides")
+    );
+    assert_eq!(
+        relative_dir(Path::new("/app/web"), Path::new("/app/shared")),
+        Path::new("../shared")
+    );
+    assert_eq!(
+        relative_dir(Path::new("/app"), Path::new("/app")),
+        Path::new("")
+    );
+}
@@ -40,9 +40,14 @@ pub struct Service {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub devices: Vec<RawOr<AliasedName>>,
 
-    /// A list of other containers to start first.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub depends_on: Vec<RawOr<String>>,
+    /// Other services to start first, and when to consider them ready.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        serialize_with = "serialize_map_or_default_list",
+        deserialize_with = "deserialize_map_or_default_list"
+    )]
+    pub depends_on: BTreeMap<String, Dependency>,
 
     /// DNS servers.
     #[serde(
@@ -107,6 +112,11 @@ pub struct Service {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub extra_hosts: Vec<RawOr<HostMapping>>,
 
+    /// How to check whether this service is healthy.  Requires version
+    /// 2.1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub healthcheck: Option<Healthcheck>,
+
     /// The name of the image to build or pull for this container.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub image: Option<RawOr<Image>>,
@@ -178,13 +188,16 @@ pub struct Service {
 
     /// The relative number of CPU shares to give to this container.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub cpu_shares: Option<u32>,
+    pub cpu_shares: Option<RawOr<u32>>,
 
     /// Limit the CFS CPU quota.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub cpu_quota: Option<u32>,
+    pub cpu_quota: Option<RawOr<u32>>,
+
+    /// The CPUs on which this container may run, such as `0-3` or `0,1`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpuset: Option<RawOr<String>>,
 
-    // TODO LOW: cpuset
     /// The domain name to use for this container.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub domainname: Option<RawOr<String>>,
@@ -211,11 +224,19 @@ pub struct Service {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub memswap_limit: Option<RawOr<MemorySize>>,
 
-    /// The MAC address to use for this container's network interface.
-    #[serde(default, skip_serializing_if = "is_false")]
-    pub privileged: bool,
+    /// How likely the kernel is to swap out this container's memory, from
+    /// 0 to 100.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mem_swappiness: Option<RawOr<u32>>,
+
+    /// Should this container be run in privileged mode?
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privileged: Option<bool>,
+
+    /// Should the container's root file system be mounted read-only?
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
 
-    // TODO LOW: read_only (what is this, anyway?)
     /// What should we do when the container exits?
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub restart: Option<RawOr<RestartMode>>,
@@ -227,13 +248,13 @@ pub struct Service {
 
     /// Should STDIN be left open when running the container?  Corresponds
     /// to `docker run -i`.
-    #[serde(default, skip_serializing_if = "is_false")]
-    pub stdin_open: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdin_open: Option<bool>,
 
     /// Should a TTY be be allocated for the container?  Corresponds to
     /// `docker run -t`.
-    #[serde(default, skip_serializing_if = "is_false")]
-    pub tty: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tty: Option<bool>,
 
     /// The user name (or UID) of the user under which to execute the
     /// container's command.  May optionally be followed by `:group` or
@@ -252,7 +273,7 @@ pub struct Service {
     /// Negative scores make a container less likely to be killed when the
     /// kernel can't find memory; positive scores make it more likely.
     #[serde(skip_serializing_if = "Option::is_none")]
-    oom_score_adj: Option<i16>,
+    oom_score_adj: Option<RawOr<i16>>,
 
     /// Extra groups to grant to the user.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -269,46 +290,50 @@ pub struct Service {
 
 derive_standard_impls_for!(Service, {
     build,
-    cap_add,
-    cap_drop,
+    cap_add with unique,
+    cap_drop with unique,
     command,
     cgroup_parent,
     container_name,
-    devices,
+    devices with key(alias_key),
     depends_on,
-    dns,
-    dns_search,
-    tmpfs,
+    dns with unique,
+    dns_search with unique,
+    tmpfs with unique,
     entrypoint,
-    env_files,
+    env_files => "env_file" with unique,
     environment,
-    expose,
+    expose with unique,
     extends,
-    external_links,
-    extra_hosts,
+    external_links with unique,
+    extra_hosts with key(host_mapping_key),
+    healthcheck,
     image,
     labels,
-    links,
+    links with key(alias_key),
     logging,
     network_mode,
     networks,
     pid,
-    ports,
-    security_opt,
+    ports with unique,
+    security_opt with unique,
     stop_signal,
     ulimits,
-    volumes,
-    volumes_from,
+    volumes with key(volume_mount_key),
+    volumes_from with unique,
     volume_driver,
     cpu_shares,
     cpu_quota,
+    cpuset,
     domainname,
     hostname,
     ipc,
     mac_address,
     mem_limit,
     memswap_limit,
+    mem_swappiness,
     privileged,
+    read_only,
     restart,
     shm_size,
     stdin_open,
@@ -316,7 +341,7 @@ derive_standard_impls_for!(Service, {
     user,
     working_dir,
     oom_score_adj,
-    group_add,
+    group_add with replace,
     _hidden
 });
 
@@ -326,7 +351,7 @@ impl Service {
     pub fn inline_all(&mut self, base: &Path) -> Result<()> {
         let mut new_env = BTreeMap::new();
         for rel_path in &self.env_files {
-            let env_file = EnvFile::load(&base.join(&rel_path.value()?))?;
+            let env_file = EnvFile::load(&base.join(rel_path.value()?))?;
             new_env.append(&mut env_file.to_environment()?);
         }
         new_env.append(&mut self.environment.clone());
@@ -355,11 +380,41 @@ fn service_env_file_is_renamed() {
 env_file:
   - foo/bar.env
 "#;
-    let service: Service = serde_yaml::from_str(&yaml).unwrap();
+    let service: Service = serde_yaml::from_str(yaml).unwrap();
     assert_eq!(service.env_files.len(), 1);
     assert_eq!(service.env_files[0], escape("foo/bar.env").unwrap());
 }
 
+#[test]
+fn service_numeric_fields_support_interpolation() {
+    let yaml = r#"---
+cpu_quota: 50000
+cpu_shares: "${SHARES}"
+"#;
+    assert_roundtrip!(Service, yaml);
+
+    let mut service: Service = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(service.cpu_quota, Some(value(50000)));
+    let mut env = BTreeMap::new();
+    env.insert("SHARES".to_owned(), "512".to_owned());
+    service.interpolate_all_env(&env).unwrap();
+    assert_eq!(service.cpu_shares, Some(value(512)));
+
+    // Interpolated values which aren't numbers are rejected.
+    let mut service: Service = serde_yaml::from_str(yaml).unwrap();
+    env.insert("SHARES".to_owned(), "lots".to_owned());
+    assert!(service.interpolate_all_env(&env).is_err());
+}
+
+#[test]
+fn service_scalar_fields_are_not_read_as_floats() {
+    let service: Service = serde_yaml::from_str("---\nhostname: 1.10\n").unwrap();
+    assert_eq!(service.hostname, Some(value("1.10".to_owned())));
+
+    // Integer fields reject floats, instead of rounding them.
+    assert!(serde_yaml::from_str::<Service>("---\ncpu_shares: 1.0\n").is_err());
+}
+
 #[test]
 fn service_networks_supports_map() {
     let yaml = r#"---
@@ -378,7 +433,7 @@ fn service_networks_supports_list() {
 "networks":
   - "backend"
 "#;
-    let service: Service = serde_yaml::from_str(&yaml).unwrap();
+    let service: Service = serde_yaml::from_str(yaml).unwrap();
     assert_eq!(service.networks.len(), 1);
     assert_eq!(
         service.networks.get("backend").unwrap(),
@@ -397,3 +452,110 @@ ulimits:
 "#;
     assert_roundtrip!(Service, yaml);
 }
+
+#[test]
+fn service_merge_follows_docker_compose_rules() {
+    // Based on the examples in "Adding and overriding configuration" in
+    // the `docker-compose` documentation.
+    let examples = vec![
+        // Single-value options are replaced.
+        (
+            "command: python app.py",
+            "command: python otherapp.py",
+            "command: python otherapp.py",
+        ),
+        (
+            "image: example/web:1.0\nmem_limit: 1g",
+            "image: example/web:2.0\nmem_limit: 2g",
+            "image: example/web:2.0\nmem_limit: 2g",
+        ),
+        (
+            "entrypoint: [\"/bin/sh\", \"-c\"]",
+            "entrypoint: [\"/entrypoint\"]",
+            "entrypoint: [\"/entrypoint\"]",
+        ),
+        // Flags are only replaced when the override sets them.
+        (
+            "privileged: true\ntty: true",
+            "tty: false",
+            "privileged: true\ntty: false",
+        ),
+        // `group_add` is replaced as a whole.
+        (
+            "group_add: [mail, audio]",
+            "group_add: [video]",
+            "group_add: [video]",
+        ),
+        // Logging options are merged only if the driver stays the same.
+        (
+            "logging: {driver: syslog, options: {syslog-address: \"tcp://192.168.0.42:123\"}}",
+            "logging: {options: {tag: web}}",
+            "logging: {driver: syslog, options: {syslog-address: \"tcp://192.168.0.42:123\", tag: web}}",
+        ),
+        (
+            "logging: {driver: syslog, options: {syslog-address: \"tcp://192.168.0.42:123\"}}",
+            "logging: {driver: json-file, options: {max-size: 10m}}",
+            "logging: {driver: json-file, options: {max-size: 10m}}",
+        ),
+        (
+            "logging: {driver: syslog, options: {syslog-address: \"tcp://192.168.0.42:123\"}}",
+            "logging: {driver: json-file}",
+            "logging: {driver: json-file}",
+        ),
+        // Multi-value options are concatenated, without duplicates.
+        (
+            "expose: [\"3000\"]",
+            "expose: [\"4000\", \"5000\"]",
+            "expose: [\"3000\", \"4000\", \"5000\"]",
+        ),
+        (
+            "ports: [\"8080:80\", \"8443:443\"]",
+            "ports: [\"8443:443\", \"9000:9000\"]",
+            "ports: [\"8080:80\", \"8443:443\", \"9000:9000\"]",
+        ),
+        (
+            "dns: 8.8.8.8\ndns_search: example.com",
+            "dns: [8.8.8.8, 8.8.4.4]\ndns_search: example.com",
+            "dns: [8.8.8.8, 8.8.4.4]\ndns_search: example.com",
+        ),
+        // `environment` and `labels` are merged by key.
+        (
+            "environment: {FOO: original, BAR: original}",
+            "environment: {BAR: local, BAZ: local}",
+            "environment: {FOO: original, BAR: local, BAZ: local}",
+        ),
+        (
+            "labels: [\"a=original\", \"b=original\"]",
+            "labels: {b: local}",
+            "labels: {a: original, b: local}",
+        ),
+        // `volumes` and `devices` are merged by container path.
+        (
+            "volumes: [\"./original:/foo\", \"./original:/bar\"]",
+            "volumes: [\"./local:/bar\", \"./local:/baz\"]",
+            "volumes: [\"./original:/foo\", \"./local:/bar\", \"./local:/baz\"]",
+        ),
+        (
+            "devices: [\"/dev/ttyUSB0:/dev/ttyUSB0\", \"/dev/ttyUSB1:/dev/ttyUSB1\"]",
+            "devices: [\"/dev/ttyUSB2:/dev/ttyUSB1\", \"/dev/sda\"]",
+            "devices: [\"/dev/ttyUSB0:/dev/ttyUSB0\", \"/dev/ttyUSB2:/dev/ttyUSB1\", \"/dev/sda\"]",
+        ),
+        // `links` are merged by alias, and `extra_hosts` by hostname.
+        (
+            "links: [\"db:database\", redis]",
+            "links: [\"postgres:database\"]",
+            "links: [\"postgres:database\", redis]",
+        ),
+        (
+            "extra_hosts: [\"somehost:162.242.195.82\"]",
+            "extra_hosts: [\"somehost:50.31.209.229\", \"otherhost:127.0.0.1\"]",
+            "extra_hosts: [\"somehost:50.31.209.229\", \"otherhost:127.0.0.1\"]",
+        ),
+    ];
+    for (base, ovr, expected) in examples {
+        let base: Service = serde_yaml::from_str(base).unwrap();
+        let ovr: Service = serde_yaml::from_str(ovr).unwrap();
+        let expected: Service = serde_yaml::from_str(expected).unwrap();
+        assert_eq!(base.merge_override(&ovr), expected, "{:?}", ovr);
+    }
+}
@@ -165,3 +165,73 @@ where
         Some(ref v) => serializer.serialize_some(&Wrap(v)),
     }
 }
+
+/// Like `deserialize_string_or_struct`, but for a list where each item may
+/// be either a string or a struct.  This is common in version 3 files,
+/// which often offer a "short syntax" and a "long syntax" for list items.
+pub fn deserialize_list_of_string_or_struct<'de, T, D>(
+    d: D,
+) -> Result<Vec<T>, D::Error>
+where
+    T: Deserialize<'de> + FromStr,
+    <T as FromStr>::Err: Display,
+    D: Deserializer<'de>,
+{
+    /// A wrapper type which deserializes a single list item using
+    /// `deserialize_string_or_struct`.  If we called `T::deserialize`
+    /// directly, we'd only support the struct syntax.
+    struct Item<T>(T);
+
+    impl<'de, T> Deserialize<'de> for Item<T>
+    where
+        T: Deserialize<'de> + FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        fn deserialize<D>(d: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserialize_string_or_struct(d).map(Item)
+        }
+    }
+
+    let items: Vec<Item<T>> = Deserialize::deserialize(d)?;
+    Ok(items.into_iter().map(|Item(item)| item).collect())
+}
+
+/// Like `serialize_string_or_struct`, but for a list of values.
+pub fn serialize_list_of_string_or_struct<T, S>(
+    values: &[T],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    T: SerializeStringOrStruct,
+    S: Serializer,
+{
+    use serde::ser::SerializeSeq;
+
+    /// The same newtype trick used by `serialize_opt_string_or_struct`.
+    struct Wrap<'a, T>(&'a T)
+    where
+        T: SerializeStringOrStruct;
+
+    impl<'a, T> Serialize for Wrap<'a, T>
+    where
+        T: 'a + SerializeStringOrStruct,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match *self {
+                Wrap(v) => serialize_string_or_struct(v, serializer),
+            }
+        }
+    }
+
+    let mut seq = serializer.serialize_seq(Some(values.len()))?;
+    for value in values {
+        seq.serialize_element(&Wrap(value))?;
+    }
+    seq.end()
+}
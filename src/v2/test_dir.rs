@@ -0,0 +1,48 @@
+//! Scratch directories for tests which need to read real files.
+
+use std::env;
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Used to give each `TestDir` in this process a different name.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A new, empty directory under `env::temp_dir()`, which is removed when
+/// it is dropped, even if the test panics.
+#[derive(Debug)]
+pub(crate) struct TestDir {
+    path: PathBuf,
+}
+
+impl TestDir {
+    /// Create a directory whose name starts with `prefix`.
+    pub(crate) fn new(prefix: &str) -> TestDir {
+        let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+        let path = env::temp_dir().join(format!(
+            "compose_yml_{}_{}_{}",
+            prefix,
+            process::id(),
+            id
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TestDir { path }
+    }
+}
+
+impl Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,364 @@
+//! Three-way merges of files which were edited independently.
+
+use serde_json::{Map, Value};
+
+use super::common::*;
+
+/// A field which was changed in different ways by both sides of
+/// `File::merge_three_way`.  The merged file uses our version of the
+/// field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeConflict {
+    /// The conflicting field.  For conflicting list items, this is the
+    /// path to the list.
+    pub path: FieldPath,
+
+    /// The value in the common base file, or `None` if it wasn't present.
+    /// Values are shown in the same form as they would be written to a
+    /// file, converted to JSON.
+    pub base: Option<Value>,
+
+    /// Our value, or `None` if we removed it.
+    pub ours: Option<Value>,
+
+    /// Their value, or `None` if they removed it.
+    pub theirs: Option<Value>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    pub _hidden: (),
+}
+
+impl MergeConflict {
+    /// Describe a conflict at `path`, which is relative to the top of the
+    /// file.
+    fn new(
+        path: &[String],
+        base: Option<&Value>,
+        ours: Option<&Value>,
+        theirs: Option<&Value>,
+    ) -> MergeConflict {
+        MergeConflict {
+            path: FieldPath::new(path),
+            base: base.cloned(),
+            ours: ours.cloned(),
+            theirs: theirs.cloned(),
+            _hidden: (),
+        }
+    }
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path)
+    }
+}
+
+/// Merge the changes from `base` to `ours` and from `base` to `theirs`.
+/// See `File::merge_three_way`.
+pub(crate) fn merge_three_way(
+    base: &File,
+    ours: &File,
+    theirs: &File,
+) -> Result<(File, Vec<MergeConflict>)> {
+    let to_value = |file: &File| -> Result<Value> {
+        let mut value =
+            serde_json::to_value(file).map_err(Error::validation_failed)?;
+        depends_on_as_maps(&mut value)?;
+        Ok(value)
+    };
+    let mut merger = Merger { conflicts: vec![] };
+    let merged = merger.merge(
+        &mut vec![],
+        Some(&to_value(base)?),
+        Some(&to_value(ours)?),
+        Some(&to_value(theirs)?),
+    );
+    let merged = merged.unwrap_or_else(|| Value::Object(Map::new()));
+    let file = serde_json::from_value(merged).map_err(Error::validation_failed)?;
+    Ok((file, merger.conflicts))
+}
+
+/// `depends_on` is written as a list when none of the dependencies have
+/// conditions.  Convert these lists to maps, so that dependencies are
+/// merged by service name, whichever form each file uses.
+fn depends_on_as_maps(file: &mut Value) -> Result<()> {
+    let default = serde_json::to_value(Dependency::default())
+        .map_err(Error::validation_failed)?;
+    let services = file.get_mut("services").and_then(Value::as_object_mut);
+    for service in services
+        .into_iter()
+        .flat_map(|services| services.values_mut())
+    {
+        let names = match service.get("depends_on") {
+            Some(Value::Array(names)) => names.clone(),
+            _ => continue,
+        };
+        let deps = names
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|name| (name.to_owned(), default.clone()))
+            .collect();
+        service["depends_on"] = Value::Object(deps);
+    }
+    Ok(())
+}
+
+/// Our merge state.
+struct Merger {
+    /// The conflicts we've found so far.
+    conflicts: Vec<MergeConflict>,
+}
+
+impl Merger {
+    /// Merge the values at `path`.  `None` means the value isn't present.
+    fn merge(
+        &mut self,
+        path: &mut Vec<String>,
+        base: Option<&Value>,
+        ours: Option<&Value>,
+        theirs: Option<&Value>,
+    ) -> Option<Value> {
+        if ours == theirs || base == theirs {
+            return ours.cloned();
+        }
+        if base == ours {
+            return theirs.cloned();
+        }
+        match (base, ours, theirs) {
+            (base, Some(Value::Object(o)), Some(Value::Object(t))) => {
+                let empty = Map::new();
+                let b = base.and_then(|b| b.as_object()).unwrap_or(&empty);
+                Some(Value::Object(self.merge_maps(path, b, o, t)))
+            }
+            (base, Some(Value::Array(o)), Some(Value::Array(t)))
+                if !matches!(list_items(path), ListItems::Atomic) =>
+            {
+                let empty = vec![];
+                let b = base.and_then(|b| b.as_array()).unwrap_or(&empty);
+                Some(Value::Array(self.merge_lists(path, b, o, t)))
+            }
+            _ => {
+                self.conflicts
+                    .push(MergeConflict::new(path, base, ours, theirs));
+                ours.cloned()
+            }
+        }
+    }
+
+    /// Merge three maps key by key.
+    fn merge_maps(
+        &mut self,
+        path: &mut Vec<String>,
+        base: &Map<String, Value>,
+        ours: &Map<String, Value>,
+        theirs: &Map<String, Value>,
+    ) -> Map<String, Value> {
+        let mut keys: Vec<&String> = base
+            .keys()
+            .chain(ours.keys())
+            .chain(theirs.keys())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        let mut result = Map::new();
+        for key in keys {
+            path.push(key.to_owned());
+            let merged =
+                self.merge(path, base.get(key), ours.get(key), theirs.get(key));
+            path.pop();
+            if let Some(merged) = merged {
+                result.insert(key.to_owned(), merged);
+            }
+        }
+        result
+    }
+
+    /// Merge three lists, matching up items using `list_items`.  We keep
+    /// the order of our list, and add their new items at the end.
+    fn merge_lists(
+        &mut self,
+        path: &mut Vec<String>,
+        base: &[Value],
+        ours: &[Value],
+        theirs: &[Value],
+    ) -> Vec<Value> {
+        let items = list_items(path);
+        let key = |item: &Value| item_key(items, item);
+        let find = |items: &'_ [Value], k: &str| -> Option<Value> {
+            items.iter().find(|item| key(item) == k).cloned()
+        };
+
+        let mut result = vec![];
+        for item in ours {
+            let k = key(item);
+            let merged = self.merge(
+                path,
+                find(base, &k).as_ref(),
+                Some(item),
+                find(theirs, &k).as_ref(),
+            );
+            result.extend(merged);
+        }
+        for item in theirs {
+            let k = key(item);
+            if ours.iter().any(|o| key(o) == k) {
+                continue;
+            }
+            let merged = self.merge(path, find(base, &k).as_ref(), None, Some(item));
+            result.extend(merged);
+        }
+        result
+    }
+}
+
+/// How to match up the items of the list at `path`.  Lists in a service
+/// use the same rules as `MergeOverride`, and all other lists are merged
+/// as single values.
+fn list_items(path: &[String]) -> ListItems {
+    match path {
+        [top, _, field] if top == "services" => Service::list_items(field),
+        _ => ListItems::Atomic,
+    }
+}
+
+/// The identity of `item` in a list whose items are matched up using
+/// `items`.
+fn item_key(items: ListItems, item: &Value) -> String {
+    let found = match items {
+        ListItems::Keyed(key_fn) => key_fn(item),
+        _ => None,
+    };
+    found
+        .map(|k| format!("key:{}", k))
+        .unwrap_or_else(|| format!("value:{}", item))
+}
+
+#[test]
+fn three_way_merge_combines_independent_changes() {
+    let base = File::from_str(
+        r#"---
+services:
+  web:
+    image: "example/web:1.0"
+    environment:
+      MODE: prod
+    volumes:
+      - "./data:/data"
+      - "./logs:/logs"
+    ports: ["80:80"]
+version: "2"
+"#,
+    )
+    .unwrap();
+    let ours = File::from_str(
+        r#"---
+services:
+  web:
+    image: "example/web:2.0"
+    environment:
+      MODE: prod
+      DEBUG: "1"
+    volumes:
+      - "./data:/data"
+      - "./our-logs:/logs"
+    ports: ["80:80", "9229:9229"]
+version: "2"
+"#,
+    )
+    .unwrap();
+    let theirs = File::from_str(
+        r#"---
+services:
+  web:
+    image: "example/web:3.0"
+    environment:
+      MODE: staging
+    volumes:
+      - "./data:/data"
+      - "./their-logs:/logs"
+      - "./cache:/cache"
+    ports: ["443:443"]
+  db:
+    image: "postgres"
+version: "2"
+"#,
+    )
+    .unwrap();
+
+    let (merged, conflicts) = File::merge_three_way(&base, &ours, &theirs).unwrap();
+    let expected = File::from_str(
+        r#"---
+services:
+  web:
+    image: "example/web:2.0"
+    environment:
+      MODE: staging
+      DEBUG: "1"
+    volumes:
+      - "./data:/data"
+      - "./our-logs:/logs"
+      - "./cache:/cache"
+    ports: ["9229:9229", "443:443"]
+  db:
+    image: "postgres"
+version: "2"
+"#,
+    )
+    .unwrap();
+    assert_eq!(merged, expected);
+
+    let fields: Vec<_> = conflicts.iter().map(|c| c.to_string()).collect();
+    assert_eq!(fields, vec!["services.web.image", "services.web.volumes"]);
+    assert_eq!(conflicts[1].base, Some(Value::from("./logs:/logs")));
+    assert_eq!(conflicts[1].theirs, Some(Value::from("./their-logs:/logs")));
+}
+
+#[test]
+fn three_way_merge_treats_commands_as_single_values() {
+    let file = |command: &str| {
+        let yaml = format!(
+            "---\nservices:\n  web:\n    command: {}\nversion: \"2\"\n",
+            command
+        );
+        File::from_str(&yaml).unwrap()
+    };
+    let base = file("[run, --fast]");
+    let ours = file("[run, --slow]");
+    let theirs = file("[run, --verbose]");
+
+    let (merged, conflicts) = File::merge_three_way(&base, &ours, &theirs).unwrap();
+    assert_eq!(merged, ours);
+    let fields: Vec<_> = conflicts.iter().map(|c| c.to_string()).collect();
+    assert_eq!(fields, vec!["services.web.command"]);
+    assert_eq!(
+        conflicts[0].theirs,
+        Some(Value::from(vec!["run", "--verbose"]))
+    );
+}
+
+#[test]
+fn three_way_merge_combines_independent_dependencies() {
+    let file = |depends_on: &str| {
+        let yaml = format!(
+            "---\nservices:\n  web:\n    depends_on: {}\nversion: \"2.1\"\n",
+            depends_on
+        );
+        File::from_str(&yaml).unwrap()
+    };
+    let base = file("[db]");
+    let ours = file("[db, cache]");
+    let theirs = file(
+        "{db: {condition: service_healthy}, queue: {condition: service_started}}",
+    );
+
+    let (merged, conflicts) = File::merge_three_way(&base, &ours, &theirs).unwrap();
+    assert!(conflicts.is_empty(), "{:?}", conflicts);
+    assert_eq!(
+        merged,
+        file("{cache: {condition: service_started}, db: {condition: service_healthy}, queue: {condition: service_started}}")
+    );
+}
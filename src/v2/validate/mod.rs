@@ -8,6 +8,12 @@ use url::Url;
 use super::File;
 use crate::errors::*;
 
+mod unsupported_fields;
+
+pub(crate) use self::unsupported_fields::{
+    find_unsupported_fields, PathElement, UnsupportedField,
+};
+
 /// Schema for `docker-compose.yml` version 2.0.
 const COMPOSE_2_0_SCHEMA_STR: &str = include_str!("config_schema_v2.0.json");
 
@@ -25,7 +31,7 @@ const COMPOSE_2_4_SCHEMA_STR: &str = include_str!("config_schema_v2.4.json");
 
 /// Load and parse a built-in JSON file, panicking if it contains invalid
 /// JSON.
-fn load_schema_json(json: &'static str) -> serde_json::Value {
+pub(crate) fn load_schema_json(json: &'static str) -> serde_json::Value {
     match serde_json::from_str(json) {
         Ok(value) => value,
         Err(err) => panic!("cannot parse built-in schema: {}", err),
@@ -54,18 +60,34 @@ lazy_static! {
     load_schema_json(COMPOSE_2_4_SCHEMA_STR);
 }
 
+/// Look up the official JSON schema for the specified version of
+/// `docker-compose.yml`.
+pub(crate) fn schema_for_version(version: &str) -> Result<&'static serde_json::Value> {
+    match version {
+        "2" => Ok(COMPOSE_2_0_SCHEMA.deref()),
+        "2.1" => Ok(COMPOSE_2_1_SCHEMA.deref()),
+        "2.2" => Ok(COMPOSE_2_2_SCHEMA.deref()),
+        "2.3" => Ok(COMPOSE_2_3_SCHEMA.deref()),
+        "2.4" => Ok(COMPOSE_2_4_SCHEMA.deref()),
+        vers => Err(Error::UnsupportedVersion(vers.to_owned())),
+    }
+}
+
 /// Validate a `File` against the official JSON schema provided by
 /// `docker-compose`.
 pub fn validate_file(file: &File) -> Result<()> {
-    let schema_value = match &file.version[..] {
-        "2" => COMPOSE_2_0_SCHEMA.deref(),
-        "2.1" => COMPOSE_2_1_SCHEMA.deref(),
-        "2.2" => COMPOSE_2_2_SCHEMA.deref(),
-        "2.3" => COMPOSE_2_3_SCHEMA.deref(),
-        "2.4" => COMPOSE_2_4_SCHEMA.deref(),
-        vers => return Err(Error::UnsupportedVersion(vers.to_owned())),
-    };
+    let schema_value = schema_for_version(&file.version)?;
+    let value = serde_json::to_value(file).map_err(Error::validation_failed)?;
+    validate_value(schema_value, &value)
+}
 
+/// Validate a serialized `docker-compose.yml` file against one of our
+/// built-in JSON schemas.  This is shared by all the file formats we
+/// support.
+pub(crate) fn validate_value(
+    schema_value: &serde_json::Value,
+    value: &serde_json::Value,
+) -> Result<()> {
     let mut scope = valico::json_schema::Scope::new();
     let id = Url::parse("http://example.com/config_schema.json")
         .expect("internal schema URL should be valid");
@@ -76,8 +98,7 @@ pub fn validate_file(file: &File) -> Result<()> {
         Err(err) => panic!("cannot parse built-in schema: {:?}", err),
     };
 
-    let value = serde_json::to_value(&file).map_err(Error::validation_failed)?;
-    let validation_state = schema.validate(&value);
+    let validation_state = schema.validate(value);
     if validation_state.is_strictly_valid() {
         Ok(())
     } else {
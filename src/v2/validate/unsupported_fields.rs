@@ -0,0 +1,256 @@
+//! Find the fields in a serialized file which aren't allowed by a JSON
+//! schema.  Unlike full validation, this only looks at which fields are
+//! present and what types they have, which is what changes between file
+//! versions.  We only support the subset of JSON schema used by our
+//! built-in schemas.
+
+use regex::Regex;
+use serde_json::Value;
+use std::fmt;
+
+/// One component of the path to a field in a serialized file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PathElement {
+    /// A key in a map.
+    Key(String),
+    /// An index in a list.
+    Index(usize),
+}
+
+impl fmt::Display for PathElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathElement::Key(key) => write!(f, "{}", key),
+            PathElement::Index(idx) => write!(f, "{}", idx),
+        }
+    }
+}
+
+/// A field which isn't allowed by a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UnsupportedField {
+    /// The path to the field.
+    pub(crate) path: Vec<PathElement>,
+    /// Is the field a map which could be replaced by a list of its keys?
+    /// This is how we downgrade things like `depends_on` conditions.
+    pub(crate) accepts_key_list: bool,
+}
+
+/// Find all the fields in `value` which aren't allowed by `schema`, in
+/// depth-first order.
+pub(crate) fn find_unsupported_fields(
+    schema: &Value,
+    value: &Value,
+) -> Vec<UnsupportedField> {
+    let mut walker = Walker {
+        root: schema,
+        found: vec![],
+    };
+    walker.walk(schema, value, &mut vec![]);
+    walker.found.dedup();
+    walker.found
+}
+
+/// Walks a value and a schema in parallel.
+struct Walker<'a> {
+    /// The top-level schema, which we need to resolve `$ref` links.
+    root: &'a Value,
+    /// The unsupported fields we've found so far.
+    found: Vec<UnsupportedField>,
+}
+
+impl<'a> Walker<'a> {
+    /// Follow any `$ref` links in `schema`.
+    fn resolve(&self, mut schema: &'a Value) -> &'a Value {
+        while let Some(Value::String(link)) = schema.get("$ref") {
+            schema = link
+                .strip_prefix('#')
+                .and_then(|ptr| self.root.pointer(ptr))
+                .unwrap_or_else(|| panic!("cannot resolve schema link {:?}", link));
+        }
+        schema
+    }
+
+    /// The branches of a `oneOf` or `anyOf` in `schema`, if any.
+    fn branches(&self, schema: &'a Value) -> Option<&'a Vec<Value>> {
+        schema
+            .get("oneOf")
+            .or_else(|| schema.get("anyOf"))
+            .and_then(|branches| branches.as_array())
+    }
+
+    /// Could `value` possibly match `schema`, based on its type?
+    fn could_match(&self, schema: &'a Value, value: &Value) -> bool {
+        let schema = self.resolve(schema);
+        if let Some(branches) = self.branches(schema) {
+            if !branches.iter().any(|b| self.could_match(b, value)) {
+                return false;
+            }
+        }
+        type_matches(schema, value)
+    }
+
+    /// Would `schema` accept a list of strings?
+    fn accepts_string_list(&self, schema: &'a Value) -> bool {
+        let schema = self.resolve(schema);
+        if let Some(branches) = self.branches(schema) {
+            return branches.iter().any(|b| self.accepts_string_list(b));
+        }
+        let list = Value::Array(vec![Value::String(String::new())]);
+        type_matches(schema, &list)
+            && schema
+                .get("items")
+                .map(|items| self.could_match(items, &list[0]))
+                .unwrap_or(true)
+    }
+
+    /// Record an unsupported field.
+    fn report(&mut self, schema: &'a Value, value: &Value, path: &[PathElement]) {
+        self.found.push(UnsupportedField {
+            path: path.to_owned(),
+            accepts_key_list: value.is_object() && self.accepts_string_list(schema),
+        });
+    }
+
+    /// Find the schema for `key` in an object matching `schema`, or `None`
+    /// if `key` isn't allowed.
+    fn property_schema(&self, schema: &'a Value, key: &str) -> Option<&'a Value> {
+        if let Some(prop) = schema.get("properties").and_then(|p| p.get(key)) {
+            return Some(prop);
+        }
+        if let Some(Value::Object(patterns)) = schema.get("patternProperties") {
+            for (pattern, prop) in patterns {
+                let re = Regex::new(pattern).expect("invalid pattern in schema");
+                if re.is_match(key) {
+                    return Some(prop);
+                }
+            }
+        }
+        match schema.get("additionalProperties") {
+            Some(Value::Bool(false)) => None,
+            Some(prop @ Value::Object(_)) => Some(prop),
+            _ => Some(&Value::Null),
+        }
+    }
+
+    /// Walk `value` and `schema` in parallel, recording any unsupported
+    /// fields.
+    fn walk(&mut self, schema: &'a Value, value: &Value, path: &mut Vec<PathElement>) {
+        let schema = self.resolve(schema);
+
+        if let Some(branches) = self.branches(schema) {
+            // Use whichever possible branch has the fewest problems.
+            let best = branches
+                .iter()
+                .filter(|b| self.could_match(b, value))
+                .map(|b| {
+                    let mut walker = Walker {
+                        root: self.root,
+                        found: vec![],
+                    };
+                    walker.walk(b, value, &mut path.clone());
+                    walker.found
+                })
+                .min_by_key(|found| found.len());
+            match best {
+                Some(found) => self.found.extend(found),
+                None => return self.report(schema, value, path),
+            }
+        }
+        if let Some(Value::Array(all)) = schema.get("allOf") {
+            for sub in all {
+                self.walk(sub, value, path);
+            }
+        }
+
+        if !type_matches(schema, value) || !enum_matches(schema, value) {
+            return self.report(schema, value, path);
+        }
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map {
+                    path.push(PathElement::Key(key.to_owned()));
+                    match self.property_schema(schema, key) {
+                        Some(prop) => self.walk(prop, child, path),
+                        None => self.report(&Value::Null, child, path),
+                    }
+                    path.pop();
+                }
+            }
+            Value::Array(items) => {
+                if let Some(item_schema @ Value::Object(_)) = schema.get("items") {
+                    for (idx, item) in items.iter().enumerate() {
+                        path.push(PathElement::Index(idx));
+                        self.walk(item_schema, item, path);
+                        path.pop();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Does `value` have one of the types allowed by `schema`?
+fn type_matches(schema: &Value, value: &Value) -> bool {
+    match schema.get("type") {
+        Some(Value::String(ty)) => has_type(ty, value),
+        Some(Value::Array(tys)) => tys
+            .iter()
+            .any(|ty| ty.as_str().map(|ty| has_type(ty, value)).unwrap_or(false)),
+        _ => true,
+    }
+}
+
+/// Does `value` have the JSON schema type `ty`?
+fn has_type(ty: &str, value: &Value) -> bool {
+    match (ty, value) {
+        ("null", Value::Null)
+        | ("boolean", Value::Bool(_))
+        | ("number", Value::Number(_))
+        | ("string", Value::String(_))
+        | ("array", Value::Array(_))
+        | ("object", Value::Object(_)) => true,
+        ("integer", Value::Number(n)) => n.is_i64() || n.is_u64(),
+        _ => false,
+    }
+}
+
+/// Is `value` one of the values allowed by `schema`?
+fn enum_matches(schema: &Value, value: &Value) -> bool {
+    match schema.get("enum") {
+        Some(Value::Array(allowed)) => allowed.contains(value),
+        _ => true,
+    }
+}
+
+#[test]
+fn unsupported_fields_are_found() {
+    let schema = serde_json::json!({
+        "type": "object",
+        "properties": {
+            "depends_on": {
+                "oneOf": [
+                    {"type": "array", "items": {"type": "string"}},
+                    {"type": "object", "additionalProperties": false}
+                ]
+            },
+            "ports": {"type": "array", "items": {"type": "string"}}
+        },
+        "additionalProperties": false
+    });
+    let value = serde_json::json!({
+        "depends_on": {"db": {}},
+        "healthcheck": {},
+        "ports": ["80", {"target": 80}]
+    });
+    let found = find_unsupported_fields(&schema, &value);
+    let paths = found
+        .iter()
+        .map(|f| {
+            let path: Vec<_> = f.path.iter().map(|p| p.to_string()).collect();
+            path.join(".")
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(paths, vec!["depends_on.db", "healthcheck", "ports.1"]);
+}
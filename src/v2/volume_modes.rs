@@ -1,9 +1,10 @@
 use super::common::*;
 
 /// Mount modes on volumes that are mapped into the Docker container.
-#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default)]
 pub enum VolumeModes {
     /// This volume can be read and written (default).
+    #[default]
     ReadWrite,
     /// This volume is read-only.
     ReadOnly,
@@ -15,12 +16,6 @@ pub enum VolumeModes {
     Delegated,
 }
 
-impl Default for VolumeModes {
-    fn default() -> VolumeModes {
-        VolumeModes::ReadWrite
-    }
-}
-
 impl fmt::Display for VolumeModes {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
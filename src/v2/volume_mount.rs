@@ -188,6 +188,13 @@ impl VolumeMount {
 
 impl_interpolatable_value!(VolumeMount);
 
+/// Identify a volume mount by its container path, if we know it.  When
+/// merging, a mount in an override replaces any mount with the same
+/// container path.
+pub(crate) fn volume_mount_key(mount: &RawOr<VolumeMount>) -> Option<String> {
+    mount.value().ok().map(|m| m.container.clone())
+}
+
 impl fmt::Display for VolumeMount {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // We can't have mode on a purely internal volume, if I'm
@@ -196,9 +203,8 @@ impl fmt::Display for VolumeMount {
             return Err(fmt::Error);
         }
 
-        match &self.host {
-            Some(host) => write!(f, "{}:", host)?,
-            None => {}
+        if let Some(host) = &self.host {
+            write!(f, "{}:", host)?;
         }
 
         write!(f, "{}", &self.container)?;
@@ -0,0 +1,287 @@
+//! Swarm deployment options.
+
+use super::common::*;
+
+/// How a service should be deployed and run when used with Docker Swarm.
+/// This is ignored by `docker-compose up`, except for `resources` when
+/// running in compatibility mode.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Deploy {
+    /// Either `replicated` (the default) or `global`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<RawOr<String>>,
+
+    /// How clients should discover this service: either `vip` or `dnsrr`.
+    /// Requires version 3.2.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint_mode: Option<RawOr<String>>,
+
+    /// The number of containers to run for a `replicated` service.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replicas: Option<RawOr<u32>>,
+
+    /// Docker labels for the service itself, not its containers.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_map_or_key_value_list"
+    )]
+    pub labels: BTreeMap<String, RawOr<String>>,
+
+    /// How to roll back a failed update.  Requires version 3.7.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollback_config: Option<UpdateConfig>,
+
+    /// How to update the service.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_config: Option<UpdateConfig>,
+
+    /// Resource limits and reservations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<Resources>,
+
+    /// When to restart containers.  This replaces the service-level
+    /// `restart` option.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart_policy: Option<RestartPolicy>,
+
+    /// Where containers should be placed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placement: Option<Placement>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(Deploy, {
+    mode,
+    endpoint_mode,
+    replicas,
+    labels,
+    rollback_config,
+    update_config,
+    resources,
+    restart_policy,
+    placement,
+    _hidden
+});
+
+/// How to update (or roll back) the containers in a service.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateConfig {
+    /// The number of containers to update at a time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parallelism: Option<RawOr<u32>>,
+
+    /// How long to wait between updating groups of containers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay: Option<RawOr<String>>,
+
+    /// What to do if an update fails: `continue`, `rollback` or `pause`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_action: Option<RawOr<String>>,
+
+    /// How long to monitor each updated container for failure.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<RawOr<String>>,
+
+    // TODO LOW: max_failure_ratio (a float, so we can't derive `Eq`).
+    /// Either `stop-first` or `start-first`.  Requires version 3.4.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<RawOr<String>>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(UpdateConfig, {
+    parallelism, delay, failure_action, monitor, order, _hidden
+});
+
+/// Resource limits and reservations for a service.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Resources {
+    /// The most resources a container may use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<ResourceSpec>,
+
+    /// The resources which must be available before a container will be
+    /// scheduled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reservations: Option<ResourceSpec>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(Resources, {
+    limits, reservations, _hidden
+});
+
+/// An amount of CPU and memory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ResourceSpec {
+    /// A number of CPUs, as a decimal string like `"0.5"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<RawOr<String>>,
+
+    /// An amount of memory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<RawOr<MemorySize>>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(ResourceSpec, {
+    cpus, memory, _hidden
+});
+
+/// When Swarm should restart a service's containers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RestartPolicy {
+    /// One of `none`, `on-failure` or `any` (the default).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<RawOr<String>>,
+
+    /// How long to wait between restart attempts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay: Option<RawOr<String>>,
+
+    /// How many times to try restarting a container before giving up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_attempts: Option<RawOr<u32>>,
+
+    /// How long to wait before deciding if a restart has succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<RawOr<String>>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(RestartPolicy, {
+    condition, delay, max_attempts, window, _hidden
+});
+
+/// Constraints on which Swarm nodes may run a service's containers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Placement {
+    /// Constraints like `node.role == manager`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub constraints: Vec<RawOr<String>>,
+
+    /// Strategies for spreading containers across nodes.  Requires
+    /// version 3.3.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub preferences: Vec<PlacementPreference>,
+
+    /// The maximum number of replicas to place on any one node.  Requires
+    /// version 3.8.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_replicas_per_node: Option<RawOr<u32>>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(Placement, {
+    constraints, preferences, max_replicas_per_node, _hidden
+});
+
+/// A placement preference.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlacementPreference {
+    /// Spread containers evenly over the values of this node label.
+    pub spread: RawOr<String>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(PlacementPreference, {
+    spread, _hidden
+});
+
+#[test]
+fn deploy_can_be_roundtripped() {
+    let yaml = r#"---
+labels:
+  com.example.description: "This label will appear on the web service"
+mode: replicated
+placement:
+  constraints:
+    - node.role == manager
+  preferences:
+    - spread: node.labels.zone
+replicas: 6
+resources:
+  limits:
+    cpus: "0.50"
+    memory: 50m
+  reservations:
+    memory: 20m
+restart_policy:
+  condition: on-failure
+  delay: 5s
+  max_attempts: 3
+update_config:
+  delay: 10s
+  parallelism: 2
+"#;
+    assert_roundtrip!(Deploy, yaml);
+}
+
+#[test]
+fn deploy_resources_accept_uppercase_memory_units() {
+    let yaml = r#"---
+resources:
+  limits:
+    memory: 50M
+"#;
+    let deploy: Deploy = serde_yaml::from_str(yaml).unwrap();
+    let limits = deploy.resources.unwrap().limits.unwrap();
+    assert_eq!(limits.memory.unwrap().value().unwrap(), &MemorySize::mb(50));
+}
@@ -0,0 +1,216 @@
+use super::common::*;
+
+/// A version 3 `docker-compose.yml` file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct File {
+    /// The version of the `docker-compose.yml` file format.  Must be 3.x.
+    pub version: String,
+
+    /// The individual services which make up this app.
+    pub services: BTreeMap<String, Service>,
+
+    /// Named volumes used by this app.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_map_struct_or_null"
+    )]
+    pub volumes: BTreeMap<String, Volume>,
+
+    /// The networks used by this app.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_map_struct_or_null"
+    )]
+    pub networks: BTreeMap<String, Network>,
+
+    /// Secrets which may be granted to services.  Requires version 3.1.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub secrets: BTreeMap<String, FileObject>,
+
+    /// Configuration files which may be granted to services.  Requires
+    /// version 3.3.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub configs: BTreeMap<String, FileObject>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(File, {
+    version, services, volumes, networks, secrets, configs, _hidden
+});
+
+impl File {
+    /// Read a file from an input stream containing YAML.
+    pub fn read<R>(r: R) -> Result<Self>
+    where
+        R: io::Read,
+    {
+        let file = serde_yaml::from_reader(r)?;
+        validate_file(&file)?;
+        Ok(file)
+    }
+
+    /// Write a file to an output stream as YAML.
+    pub fn write<W>(&self, w: &mut W) -> Result<()>
+    where
+        W: io::Write,
+    {
+        validate_file(self)?;
+        Ok(serde_yaml::to_writer(w, self)?)
+    }
+
+    /// Write a file to an output stream as YAML, showing values which were
+    /// interpolated from sensitive variables in their redacted form.  This
+    /// is intended for logging and debugging, so unlike `write`, it doesn't
+    /// validate the file.  See `InterpolateOptions::sensitive`.
+    pub fn write_redacted<W>(&self, w: &mut W) -> Result<()>
+    where
+        W: io::Write,
+    {
+        Ok(with_redaction(|| serde_yaml::to_writer(w, self))?)
+    }
+
+    /// Read a file from the specified path.
+    pub fn read_from_path<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let f = fs::File::open(path)
+            .map_err(|err| Error::read_file(path.to_owned(), err))?;
+        Self::read(io::BufReader::new(f))
+            .map_err(|err| Error::read_file(path.to_owned(), err))
+    }
+
+    /// Write a file to the specified path.
+    pub fn write_to_path<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let f = fs::File::create(path)
+            .map_err(|err| Error::write_file(path.to_owned(), err))?;
+        self.write(&mut io::BufWriter::new(f))
+            .map_err(|err| Error::write_file(path.to_owned(), err))
+    }
+}
+
+impl Default for File {
+    fn default() -> File {
+        File {
+            version: "3.9".to_owned(),
+            services: Default::default(),
+            volumes: Default::default(),
+            networks: Default::default(),
+            secrets: Default::default(),
+            configs: Default::default(),
+            _hidden: (),
+        }
+    }
+}
+
+impl FromStr for File {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<File> {
+        Self::read(io::Cursor::new(s))
+    }
+}
+
+#[test]
+fn file_can_be_converted_from_and_to_yaml_version_3() {
+    let yaml = r#"---
+services:
+  web:
+    image: "example/web:latest"
+    ports:
+      - "80:8080"
+version: "3"
+volumes:
+  db:
+    external: true
+"#;
+    assert_roundtrip!(File, yaml);
+
+    let file = File::from_str(yaml).unwrap();
+    let web = file.services.get("web").unwrap();
+    assert_eq!(
+        web.image.as_ref().unwrap().value().unwrap(),
+        &Image::new("example/web:latest").unwrap()
+    );
+}
+
+#[test]
+fn file_supports_swarm_features_in_version_3_9() {
+    let yaml = r#"---
+configs:
+  nginx:
+    file: ./nginx.conf
+secrets:
+  db_password:
+    external: true
+services:
+  web:
+    configs:
+      - source: nginx
+        target: /etc/nginx/nginx.conf
+    deploy:
+      replicas: 2
+      resources:
+        limits:
+          cpus: "0.5"
+          memory: 50m
+    image: nginx
+    ports:
+      - mode: host
+        published: 8080
+        target: 80
+    secrets:
+      - db_password
+    volumes:
+      - type: bind
+        source: ./static
+        target: /static
+        read_only: true
+version: "3.9"
+"#;
+    assert_roundtrip!(File, yaml);
+    let file = File::from_str(yaml).unwrap();
+    let web = file.services.get("web").unwrap();
+    assert_eq!(web.deploy.as_ref().unwrap().replicas, Some(value(2)));
+}
+
+#[test]
+fn file_checks_version_number() {
+    let yaml = r#"---
+"services":
+  "foo":
+    "image": "hello"
+"version": "2"
+"#;
+    assert!(File::from_str(yaml).is_err());
+}
+
+#[test]
+fn file_checks_fields_against_version_schema() {
+    // `secrets` were added in version 3.1.
+    let yaml = r#"---
+"secrets":
+  "password":
+    "file": "./password.txt"
+"services":
+  "foo":
+    "image": "hello"
+"version": "3.0"
+"#;
+    assert!(File::from_str(yaml).is_err());
+}
@@ -0,0 +1,68 @@
+use super::common::*;
+
+/// A top-level `secrets` or `configs` entry, which makes the contents of a
+/// file available to services.  Secrets and configs have identical
+/// formats, so we use the same type for both.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct FileObject {
+    /// The name of this object in Docker, if it's different from the name
+    /// we use to refer to it.  Requires version 3.5.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<RawOr<String>>,
+
+    /// The local file containing this object's contents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<RawOr<PathBuf>>,
+
+    /// If this is true, then this object was created outside of
+    /// `docker-compose`.  This option is mutually exclusive with `file`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external: Option<bool>,
+
+    /// Docker labels for this object, specifying various sorts of custom
+    /// metadata.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_map_or_key_value_list"
+    )]
+    pub labels: BTreeMap<String, RawOr<String>>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(FileObject, {
+    name, file, external, labels, _hidden
+});
+
+#[test]
+fn file_object_can_be_roundtripped() {
+    let yaml = r#"---
+file: ./secret.txt
+labels:
+  com.example: foo
+"#;
+    assert_roundtrip!(FileObject, yaml);
+}
+
+#[test]
+fn external_file_object_can_be_roundtripped() {
+    let yaml = r#"---
+external: true
+name: production_password
+"#;
+    assert_roundtrip!(FileObject, yaml);
+}
+
+#[test]
+fn file_object_rejects_compose_spec_fields() {
+    let yaml = "---\nenvironment: API_KEY\n";
+    assert!(serde_yaml::from_str::<FileObject>(yaml).is_err());
+}
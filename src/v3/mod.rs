@@ -0,0 +1,82 @@
+//! Support for the `docker-compose.yml` version 3 file format.
+//!
+//! Version 3 files were designed to work with both `docker-compose` and
+//! Docker Swarm's `docker stack deploy`.  They drop a number of
+//! single-host features found in version 2 (like `extends` and
+//! `volumes_from`), and add Swarm-oriented features like `deploy`,
+//! `secrets` and `configs`.
+//!
+//! Wherever the two formats overlap, we re-use the types from
+//! `compose_yml::v2`, so that code which manipulates images, ports and
+//! other leaf values can work with either format.
+
+// Re-export errors here so that people can use them by including `use
+// compose_yml::v3`.
+pub use crate::errors::*;
+
+// Re-export the types we share with version 2 files.
+pub use crate::v2::{
+    escape, raw, value, AliasedName, Build, CommandLine, Context, DotEnvEnvironment,
+    Environment, ExternalNetwork, FieldPath, GitUrl, Healthcheck, HostMapping,
+    HostVolume, Image, ImageVersion, InterpolateAll, InterpolateOptions,
+    InterpolationRecord, IpcMode, LayeredEnvironment, Logging, MemorySize,
+    MergeOverride, NetworkInterface, NetworkMode, OsEnvironment, PidMode, PortMapping,
+    Ports, Protocol, RawOr, RawRewrite, RawVisitor, RawVisitorMut, RegistryHost,
+    RestartMode, SensitiveVariables, Ulimit, UndefinedVariable, VariableReference,
+    VariableSource, VariableUse, VolumeModes, VolumeMount,
+};
+
+#[macro_use]
+mod validate;
+
+// Basic file structure.
+mod file;
+mod file_object;
+mod network;
+mod service;
+mod volume;
+
+// Service-related types.
+mod deploy;
+mod object_reference;
+mod service_port;
+mod service_volume;
+
+// Re-export from our child modules.
+pub use deploy::*;
+pub use file::*;
+pub use file_object::*;
+pub use network::*;
+pub use object_reference::*;
+pub use service::*;
+pub use service_port::*;
+pub use service_volume::*;
+pub use volume::*;
+
+mod common {
+    pub(crate) use serde::{Deserialize, Serialize, Serializer};
+    pub(crate) use std::collections::BTreeMap;
+    pub(crate) use std::fs;
+    pub(crate) use std::io;
+    pub(crate) use std::path::{Path, PathBuf};
+    pub(crate) use std::result;
+    pub(crate) use std::str::FromStr;
+
+    pub(crate) use crate::v2::helpers::{
+        deserialize_item_or_list, deserialize_map_or_default_list,
+        deserialize_map_or_key_value_list, deserialize_map_struct_or_null, is_false,
+    };
+    pub(crate) use crate::v2::interpolation::with_redaction;
+    pub(crate) use crate::v2::string_or_struct::{
+        deserialize_list_of_string_or_struct, deserialize_opt_string_or_struct,
+        serialize_list_of_string_or_struct, serialize_opt_string_or_struct,
+        SerializeStringOrStruct,
+    };
+    pub(crate) use crate::v2::true_or_struct::{
+        deserialize_opt_true_or_struct, serialize_opt_true_or_struct,
+    };
+    pub(crate) use crate::v2::{alias_key, host_mapping_key, volume_mount_key};
+
+    pub(crate) use super::validate::validate_file;
+    pub(crate) use super::*;
+}
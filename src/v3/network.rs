@@ -0,0 +1,70 @@
+use super::common::*;
+
+/// A network which can be used by services.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Network {
+    /// The name of this network in Docker, if it's different from the
+    /// name we use to refer to it.  Requires version 3.5.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<RawOr<String>>,
+
+    /// The name of the network driver to use.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driver: Option<RawOr<String>>,
+
+    /// Options to pass to the network driver.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub driver_opts: BTreeMap<String, RawOr<String>>,
+
+    /// Mutually-exclusive with all other options.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_opt_true_or_struct",
+        deserialize_with = "deserialize_opt_true_or_struct"
+    )]
+    pub external: Option<ExternalNetwork>,
+
+    /// Create a network which has no access to the outside world.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub internal: bool,
+
+    /// Allow standalone containers to attach to this network, in addition
+    /// to Swarm services.  Requires version 3.2.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub attachable: bool,
+
+    /// Docker labels for this network, specifying various sorts of
+    /// custom metadata.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_map_or_key_value_list"
+    )]
+    pub labels: BTreeMap<String, RawOr<String>>,
+
+    // TODO LOW: ipam
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(Network, {
+    name, driver, driver_opts, external, internal, attachable, labels, _hidden
+});
+
+#[test]
+fn network_handles_overlay_driver_correctly() {
+    let yaml = r#"---
+attachable: true
+driver: overlay
+labels:
+  com.example: foo
+"#;
+    assert_roundtrip!(Network, yaml);
+}
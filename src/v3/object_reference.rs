@@ -0,0 +1,137 @@
+use super::common::*;
+
+/// A reference from a service to a top-level `secrets` or `configs` entry.
+/// This may be written using either a short syntax (just the name of the
+/// object) or a long syntax (a struct).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ObjectReference {
+    /// The name of the top-level `secrets` or `configs` entry.
+    pub source: RawOr<String>,
+
+    /// Where to mount the object inside the container.  For secrets,
+    /// this is a file name relative to `/run/secrets`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<RawOr<String>>,
+
+    /// The numeric user ID which should own the mounted file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uid: Option<RawOr<String>>,
+
+    /// The numeric group ID which should own the mounted file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gid: Option<RawOr<String>>,
+
+    /// The permissions of the mounted file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<u32>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(ObjectReference, {
+    source, target, uid, gid, mode, _hidden
+});
+
+/// Identify a `secrets` or `configs` reference by its `source`, if we know
+/// it.  When merging, a reference in an override replaces any reference
+/// with the same `source`.
+pub(crate) fn object_reference_key(reference: &ObjectReference) -> Option<String> {
+    reference.source.value().ok().cloned()
+}
+
+impl ObjectReference {
+    /// Refer to a secret or config using only its name.  To override
+    /// other fields, you can use struct notation.
+    ///
+    /// ```
+    /// use compose_yml::v3 as dc;
+    ///
+    /// dc::ObjectReference::new("db_password");
+    ///
+    /// dc::ObjectReference {
+    ///   target: Some(dc::escape("password").unwrap()),
+    ///   ..dc::ObjectReference::new("db_password")
+    /// };
+    /// ```
+    pub fn new<S: Into<String>>(source: S) -> ObjectReference {
+        ObjectReference {
+            source: value(source.into()),
+            target: Default::default(),
+            uid: Default::default(),
+            gid: Default::default(),
+            mode: Default::default(),
+            _hidden: (),
+        }
+    }
+}
+
+impl FromStr for ObjectReference {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(ObjectReference {
+            source: raw(s)?,
+            ..ObjectReference::new("")
+        })
+    }
+}
+
+impl SerializeStringOrStruct for ObjectReference {
+    fn serialize_string_or_struct<S>(
+        &self,
+        serializer: S,
+    ) -> result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.target.is_none()
+            && self.uid.is_none()
+            && self.gid.is_none()
+            && self.mode.is_none()
+        {
+            self.source.serialize(serializer)
+        } else {
+            self.serialize(serializer)
+        }
+    }
+}
+
+#[test]
+fn object_reference_has_a_string_representation() {
+    let reference = ObjectReference::from_str("db_password").unwrap();
+    assert_eq!(reference, ObjectReference::new("db_password"));
+}
+
+#[test]
+fn object_references_may_be_strings_or_structs() {
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Wrapper {
+        #[serde(
+            deserialize_with = "deserialize_list_of_string_or_struct",
+            serialize_with = "serialize_list_of_string_or_struct"
+        )]
+        secrets: Vec<ObjectReference>,
+    }
+
+    let yaml = r#"---
+secrets:
+  - db_password
+  - mode: 288
+    source: api_key
+    target: key.txt
+"#;
+    let wrapper: Wrapper = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(wrapper.secrets[0], ObjectReference::new("db_password"));
+    assert_eq!(
+        wrapper.secrets[1].target.as_ref().unwrap().value().unwrap(),
+        "key.txt"
+    );
+    assert_roundtrip!(Wrapper, yaml);
+}
@@ -0,0 +1,354 @@
+use super::common::*;
+
+/// A service which will be managed by `docker-compose` or Docker Swarm.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Service {
+    /// How to build an image for this service.  This is ignored by Docker
+    /// Swarm.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_opt_string_or_struct",
+        deserialize_with = "deserialize_opt_string_or_struct"
+    )]
+    pub build: Option<Build>,
+
+    /// A list of capability names to grant to this container.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cap_add: Vec<RawOr<String>>,
+
+    /// A list of capability names to revoke from this container.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cap_drop: Vec<RawOr<String>>,
+
+    /// The command-line to run when launching the container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<CommandLine>,
+
+    /// The name of an optional parent cgroup.  (Mysterious.)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cgroup_parent: Option<RawOr<String>>,
+
+    /// Configuration files to make available to this service.  Requires
+    /// version 3.3.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_list_of_string_or_struct",
+        deserialize_with = "deserialize_list_of_string_or_struct"
+    )]
+    pub configs: Vec<ObjectReference>,
+
+    /// An optional (global, non-scalable) container name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_name: Option<RawOr<String>>,
+
+    /// How to deploy this service to a Swarm.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deploy: Option<Deploy>,
+
+    /// A list of devices to map into this container.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub devices: Vec<RawOr<AliasedName>>,
+
+    /// A list of other containers to start first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<RawOr<String>>,
+
+    /// DNS servers.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_item_or_list"
+    )]
+    pub dns: Vec<RawOr<String>>,
+
+    /// Domains to search for hostnames.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_item_or_list"
+    )]
+    pub dns_search: Vec<RawOr<String>>,
+
+    /// Locations to mount temporary file systems.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_item_or_list"
+    )]
+    pub tmpfs: Vec<RawOr<String>>,
+
+    /// The entrypoint for the container (wraps `command`, basically).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entrypoint: Option<CommandLine>,
+
+    /// Environment files used to supply variables to the container.  Note
+    /// that this is `env_file` in the underlying Docker format, but the
+    /// singular form looks weird at the API level.
+    #[serde(
+        rename = "env_file",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_item_or_list"
+    )]
+    pub env_files: Vec<RawOr<PathBuf>>,
+
+    /// Environment variables and values to supply to the container.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_map_or_key_value_list"
+    )]
+    pub environment: BTreeMap<String, RawOr<String>>,
+
+    /// Expose a list of ports to any containers that link to us.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expose: Vec<RawOr<String>>,
+
+    /// Links to external containers.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub external_links: Vec<RawOr<AliasedName>>,
+
+    /// Mappings for extra hosts in /etc/hosts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_hosts: Vec<RawOr<HostMapping>>,
+
+    /// How to check whether this service is healthy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub healthcheck: Option<Healthcheck>,
+
+    /// The name of the image to build or pull for this container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<RawOr<Image>>,
+
+    /// Run an init process inside the container which forwards signals
+    /// and reaps processes.  Requires version 3.7.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init: Option<bool>,
+
+    /// Docker labels for this container, specifying various sorts of
+    /// custom metadata.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_map_or_key_value_list"
+    )]
+    pub labels: BTreeMap<String, RawOr<String>>,
+
+    /// Links to other services in this file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<RawOr<AliasedName>>,
+
+    /// Logging options for this container.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logging: Option<Logging>,
+
+    /// What networking mode should we use?
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_mode: Option<RawOr<NetworkMode>>,
+
+    /// Networks to which this container is attached.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_map_or_default_list"
+    )]
+    pub networks: BTreeMap<String, NetworkInterface>,
+
+    /// What PID namespacing mode should we use?
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<RawOr<PidMode>>,
+
+    /// What ports do we want to map to our host system?
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<ServicePort>,
+
+    /// Should the container's root file system be mounted read-only?
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+
+    /// Secrets to make available to this service.  Requires version 3.1.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_list_of_string_or_struct",
+        deserialize_with = "deserialize_list_of_string_or_struct"
+    )]
+    pub secrets: Vec<ObjectReference>,
+
+    /// Security options for AppArmor or SELinux.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub security_opt: Vec<RawOr<String>>,
+
+    /// How long to wait for the container to exit after sending
+    /// `stop_signal`, before killing it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_grace_period: Option<RawOr<String>>,
+
+    /// The name of the Unix signal which will be sent to stop this
+    /// container.  Defaults to SIGTERM if not specified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_signal: Option<RawOr<String>>,
+
+    /// Kernel parameters to set in the container.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_map_or_key_value_list"
+    )]
+    pub sysctls: BTreeMap<String, RawOr<String>>,
+
+    /// Resource limits to apply to the container.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub ulimits: BTreeMap<String, Ulimit>,
+
+    /// Volumes associated with this service.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub volumes: Vec<ServiceVolume>,
+
+    /// The domain name to use for this container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domainname: Option<RawOr<String>>,
+
+    /// The hostname to use for this container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<RawOr<String>>,
+
+    /// What IPC namespacing mode should we use?
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipc: Option<RawOr<IpcMode>>,
+
+    /// The MAC address to use for this container's network interface.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mac_address: Option<RawOr<String>>,
+
+    /// Should this container be run in privileged mode?
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privileged: Option<bool>,
+
+    /// What should we do when the container exits?  This is ignored by
+    /// Docker Swarm, which uses `deploy.restart_policy` instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart: Option<RawOr<RestartMode>>,
+
+    /// The amount of shared memory to allocate for this container, in
+    /// bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shm_size: Option<RawOr<MemorySize>>,
+
+    /// Should STDIN be left open when running the container?  Corresponds
+    /// to `docker run -i`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdin_open: Option<bool>,
+
+    /// Should a TTY be be allocated for the container?  Corresponds to
+    /// `docker run -t`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tty: Option<bool>,
+
+    /// The user name (or UID) of the user under which to execute the
+    /// container's command.  May optionally be followed by `:group` or
+    /// `:gid` to specific the group or group ID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<RawOr<String>>,
+
+    /// The user namespace mode to use for this container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub userns_mode: Option<RawOr<String>>,
+
+    /// The working directory to use for this container.  This is a string,
+    /// because on Windows, it will use a different path representation
+    /// than the host OS.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<RawOr<String>>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(Service, {
+    build,
+    cap_add with unique,
+    cap_drop with unique,
+    command,
+    cgroup_parent,
+    configs with key(object_reference_key),
+    container_name,
+    deploy,
+    devices with key(alias_key),
+    depends_on with unique,
+    dns with unique,
+    dns_search with unique,
+    tmpfs with unique,
+    entrypoint,
+    env_files => "env_file" with unique,
+    environment,
+    expose with unique,
+    external_links with unique,
+    extra_hosts with key(host_mapping_key),
+    healthcheck,
+    image,
+    init,
+    labels,
+    links with key(alias_key),
+    logging,
+    network_mode,
+    networks,
+    pid,
+    ports with unique,
+    read_only,
+    secrets with key(object_reference_key),
+    security_opt with unique,
+    stop_grace_period,
+    stop_signal,
+    sysctls,
+    ulimits,
+    volumes with key(service_volume_key),
+    domainname,
+    hostname,
+    ipc,
+    mac_address,
+    privileged,
+    restart,
+    shm_size,
+    stdin_open,
+    tty,
+    user,
+    userns_mode,
+    working_dir,
+    _hidden
+});
+
+#[test]
+fn service_handles_sample_fields_correctly() {
+    let yaml = r#"---
+deploy:
+  mode: global
+image: hello
+init: true
+secrets:
+  - source: password
+    target: db_password
+stop_grace_period: 1m30s
+sysctls:
+  net.core.somaxconn: "1024"
+"#;
+    assert_roundtrip!(Service, yaml);
+}
+
+#[test]
+fn service_rejects_version_2_only_fields() {
+    let yaml = r#"---
+image: hello
+volumes_from:
+  - other
+"#;
+    assert!(serde_yaml::from_str::<Service>(yaml).is_err());
+}
@@ -0,0 +1,193 @@
+use serde::de;
+use std::fmt;
+
+use super::common::*;
+
+/// A port exposed by a service.  Version 3.2 and later support a "long
+/// syntax" for ports, which can express things like the publishing mode
+/// that can't be written using the traditional string syntax.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum ServicePort {
+    /// A port mapping written using the traditional string syntax, as in
+    /// version 2 files.
+    Short(RawOr<PortMapping>),
+
+    /// A port written using the long syntax.
+    Long(PortSpec),
+}
+
+/// We can't use `#[serde(untagged)]` to deserialize this, because it would
+/// buffer the short syntax, and `RawOr` needs to see the original text of
+/// unquoted YAML scalars.
+impl<'de> Deserialize<'de> for ServicePort {
+    fn deserialize<D>(deserializer: D) -> result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        /// Declare an internal visitor type to handle our input.
+        struct ServicePortVisitor;
+
+        impl<'de> de::Visitor<'de> for ServicePortVisitor {
+            type Value = ServicePort;
+
+            fn visit_str<E>(self, value: &str) -> result::Result<ServicePort, E>
+            where
+                E: de::Error,
+            {
+                RawOr::from_str(value)
+                    .map(ServicePort::Short)
+                    .map_err(|err| E::custom(format!("{}", err)))
+            }
+
+            fn visit_i64<E>(self, value: i64) -> result::Result<ServicePort, E>
+            where
+                E: de::Error,
+            {
+                self.visit_str(&value.to_string())
+            }
+
+            fn visit_u64<E>(self, value: u64) -> result::Result<ServicePort, E>
+            where
+                E: de::Error,
+            {
+                self.visit_str(&value.to_string())
+            }
+
+            fn visit_map<M>(self, map: M) -> result::Result<ServicePort, M::Error>
+            where
+                M: de::MapAccess<'de>,
+            {
+                let mvd = de::value::MapAccessDeserializer::new(map);
+                Deserialize::deserialize(mvd).map(ServicePort::Long)
+            }
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(formatter, "a port mapping string or a map")
+            }
+        }
+
+        deserializer.deserialize_any(ServicePortVisitor)
+    }
+}
+
+impl MergeOverride for ServicePort {}
+
+impl InterpolateAll for ServicePort {
+    fn visit_raw(&self, path: &mut Vec<String>, visitor: &mut RawVisitor<'_>) {
+        match self {
+            ServicePort::Short(mapping) => mapping.visit_raw(path, visitor),
+            ServicePort::Long(spec) => spec.visit_raw(path, visitor),
+        }
+    }
+
+    fn visit_raw_mut(
+        &mut self,
+        path: &mut Vec<String>,
+        visitor: &mut RawVisitorMut<'_>,
+    ) -> Result<()> {
+        match self {
+            ServicePort::Short(mapping) => mapping.visit_raw_mut(path, visitor),
+            ServicePort::Long(spec) => spec.visit_raw_mut(path, visitor),
+        }
+    }
+}
+
+impl From<PortMapping> for ServicePort {
+    fn from(mapping: PortMapping) -> ServicePort {
+        ServicePort::Short(value(mapping))
+    }
+}
+
+/// The long syntax for a service port.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PortSpec {
+    /// The port inside the container.
+    pub target: RawOr<u16>,
+
+    /// The publicly exposed port.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published: Option<RawOr<u16>>,
+
+    /// The protocol to use for this port.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<RawOr<Protocol>>,
+
+    /// Either `host`, to publish the port on each node, or `ingress`, to
+    /// load balance across the Swarm.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<RawOr<String>>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(PortSpec, {
+    target, published, protocol, mode, _hidden
+});
+
+impl PortSpec {
+    /// Publish the specified container port on `published`.
+    ///
+    /// ```
+    /// use compose_yml::v3 as dc;
+    ///
+    /// dc::PortSpec {
+    ///   mode: Some(dc::escape("host").unwrap()),
+    ///   ..dc::PortSpec::new(8080, 80)
+    /// };
+    /// ```
+    pub fn new(published: u16, target: u16) -> PortSpec {
+        PortSpec {
+            target: value(target),
+            published: Some(value(published)),
+            protocol: Default::default(),
+            mode: Default::default(),
+            _hidden: (),
+        }
+    }
+}
+
+#[test]
+fn service_ports_support_short_syntax() {
+    let yaml = r#"---
+- "3000"
+- "127.0.0.1:8001:8001"
+- "5000-5010:5000-5010/udp"
+"#;
+    assert_roundtrip!(Vec<ServicePort>, yaml);
+    let ports: Vec<ServicePort> = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(ports[0], ServicePort::from(PortMapping::any_to(3000)));
+
+    let ports: Vec<ServicePort> = serde_yaml::from_str("---\n- 3000\n").unwrap();
+    assert_eq!(ports[0], ServicePort::from(PortMapping::any_to(3000)));
+}
+
+#[test]
+fn service_ports_support_long_syntax() {
+    let yaml = r#"---
+- mode: host
+  protocol: udp
+  published: 8080
+  target: 80
+"#;
+    assert_roundtrip!(Vec<ServicePort>, yaml);
+    let ports: Vec<ServicePort> = serde_yaml::from_str(yaml).unwrap();
+    match &ports[0] {
+        ServicePort::Long(spec) => {
+            assert_eq!(spec.target, value(80));
+            assert_eq!(spec.published, Some(value(8080)));
+            assert_eq!(
+                spec.protocol.as_ref().unwrap().value().unwrap(),
+                &Protocol::Udp
+            );
+        }
+        other => panic!("expected long syntax, got {:?}", other),
+    }
+}
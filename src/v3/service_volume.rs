@@ -0,0 +1,289 @@
+use serde::de;
+use std::fmt;
+
+use super::common::*;
+
+/// A volume mounted by a service.  Version 3.2 and later support a "long
+/// syntax" for volumes, which can express options like `nocopy` that
+/// can't be written using the traditional string syntax.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
+pub enum ServiceVolume {
+    /// A volume written using the traditional `host:container:mode`
+    /// syntax, as in version 2 files.
+    Short(RawOr<VolumeMount>),
+
+    /// A volume written using the long syntax.
+    Long(VolumeSpec),
+}
+
+/// We can't use `#[serde(untagged)]` to deserialize this, because it would
+/// buffer the short syntax, and `RawOr` needs to see the original text of
+/// unquoted YAML scalars.
+impl<'de> Deserialize<'de> for ServiceVolume {
+    fn deserialize<D>(deserializer: D) -> result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        /// Declare an internal visitor type to handle our input.
+        struct ServiceVolumeVisitor;
+
+        impl<'de> de::Visitor<'de> for ServiceVolumeVisitor {
+            type Value = ServiceVolume;
+
+            fn visit_str<E>(self, value: &str) -> result::Result<ServiceVolume, E>
+            where
+                E: de::Error,
+            {
+                RawOr::from_str(value)
+                    .map(ServiceVolume::Short)
+                    .map_err(|err| E::custom(format!("{}", err)))
+            }
+
+            fn visit_map<M>(self, map: M) -> result::Result<ServiceVolume, M::Error>
+            where
+                M: de::MapAccess<'de>,
+            {
+                let mvd = de::value::MapAccessDeserializer::new(map);
+                Deserialize::deserialize(mvd).map(ServiceVolume::Long)
+            }
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(formatter, "a volume mount string or a map")
+            }
+        }
+
+        deserializer.deserialize_any(ServiceVolumeVisitor)
+    }
+}
+
+impl MergeOverride for ServiceVolume {}
+
+impl InterpolateAll for ServiceVolume {
+    fn visit_raw(&self, path: &mut Vec<String>, visitor: &mut RawVisitor<'_>) {
+        match self {
+            ServiceVolume::Short(mount) => mount.visit_raw(path, visitor),
+            ServiceVolume::Long(spec) => spec.visit_raw(path, visitor),
+        }
+    }
+
+    fn visit_raw_mut(
+        &mut self,
+        path: &mut Vec<String>,
+        visitor: &mut RawVisitorMut<'_>,
+    ) -> Result<()> {
+        match self {
+            ServiceVolume::Short(mount) => mount.visit_raw_mut(path, visitor),
+            ServiceVolume::Long(spec) => spec.visit_raw_mut(path, visitor),
+        }
+    }
+}
+
+impl From<VolumeMount> for ServiceVolume {
+    fn from(mount: VolumeMount) -> ServiceVolume {
+        ServiceVolume::Short(value(mount))
+    }
+}
+
+/// Volumes are identified by their path inside the container when
+/// merging, whichever syntax they use.
+pub(crate) fn service_volume_key(volume: &ServiceVolume) -> Option<String> {
+    match volume {
+        ServiceVolume::Short(mount) => volume_mount_key(mount),
+        ServiceVolume::Long(spec) => {
+            spec.target.as_ref().and_then(|t| t.value().ok().cloned())
+        }
+    }
+}
+
+/// The long syntax for a service volume.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VolumeSpec {
+    /// The kind of mount, typically `volume`, `bind` or `tmpfs`.
+    #[serde(rename = "type")]
+    pub kind: RawOr<String>,
+
+    /// The host path or volume name to mount.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<RawOr<String>>,
+
+    /// The path inside the container where the volume is mounted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<RawOr<String>>,
+
+    /// Should this volume be mounted read-only?
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+
+    /// The consistency requirements of the mount, which only matter on
+    /// Docker for Mac.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consistency: Option<RawOr<String>>,
+
+    /// Options for `bind` mounts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bind: Option<BindOptions>,
+
+    /// Options for `volume` mounts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<VolumeOptions>,
+
+    /// Options for `tmpfs` mounts.  Requires version 3.6.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmpfs: Option<TmpfsOptions>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(VolumeSpec, {
+    kind => "type", source, target, read_only, consistency, bind, volume,
+    tmpfs, _hidden
+});
+
+impl VolumeSpec {
+    /// Mount `source` at `target` using the specified kind of mount.
+    ///
+    /// ```
+    /// use compose_yml::v3 as dc;
+    ///
+    /// dc::VolumeSpec {
+    ///   read_only: Some(true),
+    ///   ..dc::VolumeSpec::new("bind", "./data", "/data")
+    /// };
+    /// ```
+    pub fn new<S1, S2, S3>(kind: S1, source: S2, target: S3) -> VolumeSpec
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<String>,
+    {
+        VolumeSpec {
+            kind: value(kind.into()),
+            source: Some(value(source.into())),
+            target: Some(value(target.into())),
+            read_only: Default::default(),
+            consistency: Default::default(),
+            bind: Default::default(),
+            volume: Default::default(),
+            tmpfs: Default::default(),
+            _hidden: (),
+        }
+    }
+}
+
+/// Options for a `bind` mount.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct BindOptions {
+    /// The bind propagation mode, like `rprivate`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub propagation: Option<RawOr<String>>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(BindOptions, {
+    propagation, _hidden
+});
+
+/// Options for a `volume` mount.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[allow(missing_copy_implementations)]
+#[serde(deny_unknown_fields)]
+pub struct VolumeOptions {
+    /// Don't copy data from the container into a new volume.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nocopy: Option<bool>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(VolumeOptions, {
+    nocopy, _hidden
+});
+
+/// Options for a `tmpfs` mount.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct TmpfsOptions {
+    /// The size of the file system, in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<RawOr<u64>>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(TmpfsOptions, {
+    size, _hidden
+});
+
+#[test]
+fn service_volumes_support_short_syntax() {
+    let yaml = r#"---
+- /var/lib/mysql
+- "./cache:/tmp/cache:ro"
+"#;
+    assert_roundtrip!(Vec<ServiceVolume>, yaml);
+    let volumes: Vec<ServiceVolume> = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(
+        volumes[0],
+        ServiceVolume::from(VolumeMount::anonymous("/var/lib/mysql"))
+    );
+}
+
+#[test]
+fn service_volumes_support_long_syntax() {
+    let yaml = r#"---
+- type: volume
+  source: mydata
+  target: /data
+  volume:
+    nocopy: true
+- type: bind
+  source: ./static
+  target: /opt/app/static
+  read_only: true
+  bind:
+    propagation: rprivate
+- type: tmpfs
+  target: /cache
+  tmpfs:
+    size: 10000
+"#;
+    assert_roundtrip!(Vec<ServiceVolume>, yaml);
+    let volumes: Vec<ServiceVolume> = serde_yaml::from_str(yaml).unwrap();
+    match &volumes[1] {
+        ServiceVolume::Long(spec) => {
+            assert_eq!(spec.kind, value("bind".to_owned()));
+            assert_eq!(spec.read_only, Some(true));
+            assert_eq!(service_volume_key(&volumes[1]).unwrap(), "/opt/app/static");
+        }
+        other => panic!("expected long syntax, got {:?}", other),
+    }
+}
@@ -0,0 +1,101 @@
+//! Support for validating a version 3 `docker-compose.yml` file against
+//! the official schema.
+
+use lazy_static::lazy_static;
+use std::ops::Deref;
+
+use super::File;
+use crate::errors::*;
+use crate::v2::validate::{load_schema_json, validate_value};
+
+/// Schema for `docker-compose.yml` version 3.0.
+const COMPOSE_3_0_SCHEMA_STR: &str = include_str!("config_schema_v3.0.json");
+
+/// Schema for `docker-compose.yml` version 3.1.
+const COMPOSE_3_1_SCHEMA_STR: &str = include_str!("config_schema_v3.1.json");
+
+/// Schema for `docker-compose.yml` version 3.2.
+const COMPOSE_3_2_SCHEMA_STR: &str = include_str!("config_schema_v3.2.json");
+
+/// Schema for `docker-compose.yml` version 3.3.
+const COMPOSE_3_3_SCHEMA_STR: &str = include_str!("config_schema_v3.3.json");
+
+/// Schema for `docker-compose.yml` version 3.4.
+const COMPOSE_3_4_SCHEMA_STR: &str = include_str!("config_schema_v3.4.json");
+
+/// Schema for `docker-compose.yml` version 3.5.
+const COMPOSE_3_5_SCHEMA_STR: &str = include_str!("config_schema_v3.5.json");
+
+/// Schema for `docker-compose.yml` version 3.6.
+const COMPOSE_3_6_SCHEMA_STR: &str = include_str!("config_schema_v3.6.json");
+
+/// Schema for `docker-compose.yml` version 3.7.
+const COMPOSE_3_7_SCHEMA_STR: &str = include_str!("config_schema_v3.7.json");
+
+/// Schema for `docker-compose.yml` version 3.8.
+const COMPOSE_3_8_SCHEMA_STR: &str = include_str!("config_schema_v3.8.json");
+
+/// Schema for `docker-compose.yml` version 3.9.
+const COMPOSE_3_9_SCHEMA_STR: &str = include_str!("config_schema_v3.9.json");
+
+lazy_static! {
+    /// Parsed schema for `docker-compose.yml` version 3.0.
+    static ref COMPOSE_3_0_SCHEMA: serde_json::Value =
+    load_schema_json(COMPOSE_3_0_SCHEMA_STR);
+
+    /// Parsed schema for `docker-compose.yml` version 3.1.
+    static ref COMPOSE_3_1_SCHEMA: serde_json::Value =
+    load_schema_json(COMPOSE_3_1_SCHEMA_STR);
+
+    /// Parsed schema for `docker-compose.yml` version 3.2.
+    static ref COMPOSE_3_2_SCHEMA: serde_json::Value =
+    load_schema_json(COMPOSE_3_2_SCHEMA_STR);
+
+    /// Parsed schema for `docker-compose.yml` version 3.3.
+    static ref COMPOSE_3_3_SCHEMA: serde_json::Value =
+    load_schema_json(COMPOSE_3_3_SCHEMA_STR);
+
+    /// Parsed schema for `docker-compose.yml` version 3.4.
+    static ref COMPOSE_3_4_SCHEMA: serde_json::Value =
+    load_schema_json(COMPOSE_3_4_SCHEMA_STR);
+
+    /// Parsed schema for `docker-compose.yml` version 3.5.
+    static ref COMPOSE_3_5_SCHEMA: serde_json::Value =
+    load_schema_json(COMPOSE_3_5_SCHEMA_STR);
+
+    /// Parsed schema for `docker-compose.yml` version 3.6.
+    static ref COMPOSE_3_6_SCHEMA: serde_json::Value =
+    load_schema_json(COMPOSE_3_6_SCHEMA_STR);
+
+    /// Parsed schema for `docker-compose.yml` version 3.7.
+    static ref COMPOSE_3_7_SCHEMA: serde_json::Value =
+    load_schema_json(COMPOSE_3_7_SCHEMA_STR);
+
+    /// Parsed schema for `docker-compose.yml` version 3.8.
+    static ref COMPOSE_3_8_SCHEMA: serde_json::Value =
+    load_schema_json(COMPOSE_3_8_SCHEMA_STR);
+
+    /// Parsed schema for `docker-compose.yml` version 3.9.
+    static ref COMPOSE_3_9_SCHEMA: serde_json::Value =
+    load_schema_json(COMPOSE_3_9_SCHEMA_STR);
+}
+
+/// Validate a `File` against the official JSON schema provided by
+/// `docker-compose`.
+pub fn validate_file(file: &File) -> Result<()> {
+    let schema_value = match &file.version[..] {
+        "3" | "3.0" => COMPOSE_3_0_SCHEMA.deref(),
+        "3.1" => COMPOSE_3_1_SCHEMA.deref(),
+        "3.2" => COMPOSE_3_2_SCHEMA.deref(),
+        "3.3" => COMPOSE_3_3_SCHEMA.deref(),
+        "3.4" => COMPOSE_3_4_SCHEMA.deref(),
+        "3.5" => COMPOSE_3_5_SCHEMA.deref(),
+        "3.6" => COMPOSE_3_6_SCHEMA.deref(),
+        "3.7" => COMPOSE_3_7_SCHEMA.deref(),
+        "3.8" => COMPOSE_3_8_SCHEMA.deref(),
+        "3.9" => COMPOSE_3_9_SCHEMA.deref(),
+        vers => return Err(Error::UnsupportedVersion(vers.to_owned())),
+    };
+    let value = serde_json::to_value(file).map_err(Error::validation_failed)?;
+    validate_value(schema_value, &value)
+}
@@ -0,0 +1,60 @@
+use super::common::*;
+
+/// A named volume which can be mounted by services.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Volume {
+    /// The name of this volume in Docker, if it's different from the name
+    /// we use to refer to it.  Requires version 3.4.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<RawOr<String>>,
+
+    /// The name of the Docker volume driver to use.  Defaults to
+    /// `"local"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub driver: Option<RawOr<String>>,
+
+    /// Key-value options to pass to the volume driver.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_map_or_key_value_list"
+    )]
+    pub driver_opts: BTreeMap<String, RawOr<String>>,
+
+    /// If this is true, then the volume was created outside of
+    /// `docker-compose`.  This option is mutually exclusive with the
+    /// `driver` options.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external: Option<bool>,
+
+    /// Docker labels for this volume, specifying various sorts of
+    /// custom metadata.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_map_or_key_value_list"
+    )]
+    pub labels: BTreeMap<String, RawOr<String>>,
+
+    /// PRIVATE.  Mark this struct as having unknown fields for future
+    /// compatibility.  This prevents direct construction and exhaustive
+    /// matching.  This needs to be be public because of
+    /// http://stackoverflow.com/q/39277157/12089
+    #[doc(hidden)]
+    #[serde(default, skip_serializing, skip_deserializing)]
+    pub _hidden: (),
+}
+
+derive_standard_impls_for!(Volume, {
+    name, driver, driver_opts, external, labels, _hidden
+});
+
+#[test]
+fn volume_with_name_can_be_converted_from_and_to_yaml() {
+    let yaml = r#"---
+driver: sample
+name: my-app-data
+"#;
+    assert_roundtrip!(Volume, yaml);
+}